@@ -1,14 +1,15 @@
-use super::context::Selector;
+use super::context::Interpolation;
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum Attribute<'a> {
     Id(&'a str),
     Class(&'a str),
     Custom(&'a str, Vec<AttributeValueComponent<'a>>),
+    Boolean(&'a str),
 }
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum AttributeValueComponent<'a> {
     RawValue(&'a str),
-    InterpolatedValue(Vec<Selector<'a>>),
+    InterpolatedValue(Interpolation<'a>),
 }
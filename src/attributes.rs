@@ -1,45 +1,165 @@
-use super::{context::Context, Attribute, AttributeValueComponent};
+use super::{
+    context::{is_truthy, Context},
+    escape, Attribute, AttributeValueComponent, Builder, NodeError,
+};
+use serde_json::Value;
+
+const BOOLEAN_ATTRIBUTES: [&str; 25] = [
+    "allowfullscreen",
+    "async",
+    "autofocus",
+    "autoplay",
+    "checked",
+    "controls",
+    "default",
+    "defer",
+    "disabled",
+    "formnovalidate",
+    "hidden",
+    "inert",
+    "ismap",
+    "itemscope",
+    "loop",
+    "multiple",
+    "muted",
+    "nomodule",
+    "novalidate",
+    "open",
+    "playsinline",
+    "readonly",
+    "required",
+    "reversed",
+    "selected",
+];
 
 pub struct Attributes<'a> {
     id: Option<&'a str>,
     classes: Vec<&'a str>,
-    custom: Vec<(&'a str, Vec<AttributeValueComponent<'a>>)>,
+    dynamic_classes: Vec<Vec<AttributeValueComponent<'a>>>,
+    custom: Vec<Attribute<'a>>,
 }
 
 impl<'a> Attributes<'a> {
-    pub fn to_html(&self, context: &Context) -> Vec<String> {
+    pub fn to_html(
+        &self,
+        context: &Context,
+        builder: &mut Builder<String, NodeError<'a>>,
+    ) -> Vec<String> {
         let mut results = vec![];
 
         if let Some(id) = self.id {
             results.push(format!("id=\"{}\"", id));
         }
 
-        if !self.classes.is_empty() {
-            results.push(format!("class=\"{}\"", self.classes.join(" ")));
+        let mut classes: Vec<String> = self.classes.iter().map(|c| c.to_string()).collect();
+        for values in self.dynamic_classes.iter() {
+            for class in dynamic_class_names(values, context, builder) {
+                if !classes.contains(&class) {
+                    classes.push(class);
+                }
+            }
         }
 
-        for (k, v) in self.custom.iter() {
-            results.push(format!(
-                "{}=\"{}\"",
-                k,
-                evaluate_attribute_value_components(&v, context)
-            ));
+        if !classes.is_empty() {
+            results.push(format!("class=\"{}\"", classes.join(" ")));
+        }
+
+        for attribute in self.custom.iter() {
+            match attribute {
+                Attribute::Boolean(k) => results.push(k.to_string()),
+                Attribute::Custom(k, v) => match v.as_slice() {
+                    [AttributeValueComponent::InterpolatedValue(interpolation)]
+                        if BOOLEAN_ATTRIBUTES.contains(k) =>
+                    {
+                        match context.evaluate(interpolation) {
+                            Ok(value) if is_truthy(&value) => results.push(k.to_string()),
+                            Ok(_) => (),
+                            Err(e) => builder.warn(e),
+                        }
+                    }
+                    _ => results.push(custom_attribute_html(k, v, context, builder)),
+                },
+                _ => (),
+            }
         }
 
         results
     }
 }
 
+fn dynamic_class_names<'a>(
+    values: &[AttributeValueComponent<'a>],
+    context: &Context,
+    builder: &mut Builder<String, NodeError<'a>>,
+) -> Vec<String> {
+    let names: Vec<String> = match values {
+        [AttributeValueComponent::InterpolatedValue(interpolation)] => {
+            match context.evaluate(interpolation) {
+                Ok(Value::String(names)) => vec![names],
+                Ok(Value::Array(names)) => names
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .map(String::from)
+                    .collect(),
+                Ok(Value::Object(toggles)) => toggles
+                    .iter()
+                    .filter(|(_, enabled)| is_truthy(enabled))
+                    .map(|(name, _)| name.to_string())
+                    .collect(),
+                Ok(_) => vec![],
+                Err(e) => {
+                    builder.warn(e);
+                    vec![]
+                }
+            }
+        }
+        values => {
+            return split_class_names(&evaluate_attribute_value_components(
+                values, context, builder,
+            ))
+        }
+    };
+
+    names
+        .iter()
+        .flat_map(|names| split_class_names(&escape::attribute_value(names)))
+        .collect()
+}
+
+fn split_class_names(names: &str) -> Vec<String> {
+    names.split_whitespace().map(String::from).collect()
+}
+
+fn custom_attribute_html<'a>(
+    name: &str,
+    values: &[AttributeValueComponent<'a>],
+    context: &Context,
+    builder: &mut Builder<String, NodeError<'a>>,
+) -> String {
+    format!(
+        "{}=\"{}\"",
+        name,
+        evaluate_attribute_value_components(values, context, builder)
+    )
+}
+
 fn evaluate_attribute_value_components<'a>(
     values: &[AttributeValueComponent<'a>],
     context: &Context,
+    builder: &mut Builder<String, NodeError<'a>>,
 ) -> String {
     values
         .iter()
         .map(|v| match v {
             AttributeValueComponent::RawValue(value) => value.to_string(),
-            AttributeValueComponent::InterpolatedValue(values) => {
-                context.interpret(values).unwrap_or("".to_string())
+            AttributeValueComponent::InterpolatedValue(interpolation) => {
+                match context.interpret(interpolation) {
+                    Ok(value) => escape::attribute_value(&value),
+                    Err(e) => {
+                        builder.warn(e);
+                        String::new()
+                    }
+                }
             }
         })
         .collect()
@@ -63,17 +183,28 @@ impl<'a> From<Vec<Attribute<'a>>> for Attributes<'a> {
             })
             .collect();
 
-        let custom = attributes
+        let dynamic_classes = attributes
             .iter()
             .filter_map(|a| match a {
-                Attribute::Custom(k, v) => Some((k.clone(), v.clone())),
+                Attribute::Custom("class", v) => Some(v.clone()),
                 _ => None,
             })
             .collect();
 
+        let custom = attributes
+            .iter()
+            .filter(|a| match a {
+                Attribute::Custom("class", _) => false,
+                Attribute::Custom(..) | Attribute::Boolean(_) => true,
+                _ => false,
+            })
+            .cloned()
+            .collect();
+
         Attributes {
             id,
             classes,
+            dynamic_classes,
             custom,
         }
     }
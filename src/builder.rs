@@ -3,12 +3,12 @@ pub struct Builder<T, E> {
     errors: Vec<E>,
 }
 
-impl<T, E> Into<Result<Vec<T>, Vec<E>>> for Builder<T, E> {
-    fn into(self) -> Result<Vec<T>, Vec<E>> {
-        if self.errors.is_empty() {
-            Ok(self.values)
+impl<T, E> From<Builder<T, E>> for Result<Vec<T>, Vec<E>> {
+    fn from(val: Builder<T, E>) -> Self {
+        if val.errors.is_empty() {
+            Ok(val.values)
         } else {
-            Err(self.errors)
+            Err(val.errors)
         }
     }
 }
@@ -55,6 +55,10 @@ impl<T, E> Builder<T, E> {
         &self.errors
     }
 
+    pub fn into_parts(self) -> (Vec<T>, Vec<E>) {
+        (self.values, self.errors)
+    }
+
     pub fn map<U, F>(self, f: F) -> Builder<U, E>
     where
         F: Fn(T) -> U,
@@ -1,4 +1,6 @@
-use super::{context::Context, flags::Flags, fragments, styles, Socket};
+use super::{
+    context::Context, flags::Flags, fragments, styles, NodeError, Severity, Socket, SocketError,
+};
 use std::io::{self, Read};
 use std::path::PathBuf;
 use std::str::FromStr;
@@ -11,24 +13,42 @@ pub fn run() {
     let fragments_root = flags
         .fragments
         .unwrap_or(PathBuf::from_str("./fragments").unwrap());
+    let fragments = fragments::new(fragments_root);
 
     match read_from_stdin() {
-        Ok(input) => {
-            if let Ok(mut parsed) = Socket::parse(&input) {
-                println!(
-                    "{}",
-                    parsed
-                        .with_fragments(&fragments::new(fragments_root))
-                        .with_styles(styles::generate(scss_entrypoint))
-                        .with_context(context)
-                        .map(|v| v.to_html())
-                        .unwrap_or("".to_string())
-                );
-            } else {
+        Ok(input) => match Socket::parse(&input) {
+            Ok(mut parsed) => match parsed
+                .with_fragments(&fragments)
+                .with_styles(styles::generate(scss_entrypoint))
+                .with_strict(flags.strict)
+                .with_context(context)
+            {
+                Ok(socket) => match socket.try_render() {
+                    Ok(rendered) => {
+                        println!("{}", rendered.html);
+                        report_errors(socket, &rendered.errors, Severity::Warning);
+                    }
+                    Err(errors) => {
+                        report_errors(socket, &errors, Severity::Error);
+                        std::process::exit(1)
+                    }
+                },
+                Err(SocketError::ContextError(e)) => {
+                    eprintln!("{}: {}", Severity::Error, e);
+                    std::process::exit(1)
+                }
+                Err(_) => println!(),
+            },
+            Err(SocketError::IncompleteParse(diagnostic))
+            | Err(SocketError::ParseError(diagnostic)) => {
+                eprintln!("{}", diagnostic.with_path("<stdin>"));
+                std::process::exit(1)
+            }
+            Err(_) => {
                 eprintln!("Unable to parse input");
                 std::process::exit(1)
             }
-        }
+        },
         Err(e) => {
             eprintln!("{}", e);
             std::process::exit(1)
@@ -36,6 +56,21 @@ pub fn run() {
     }
 }
 
+fn report_errors(socket: &Socket, errors: &[NodeError], severity: Severity) {
+    for error in errors {
+        match socket.diagnostic(error) {
+            Some(mut diagnostic) => {
+                diagnostic.severity = severity;
+                if diagnostic.path.is_none() {
+                    diagnostic = diagnostic.with_path("<stdin>");
+                }
+                eprintln!("{}", diagnostic)
+            }
+            None => eprintln!("{}: {}", severity, error),
+        }
+    }
+}
+
 fn read_from_stdin() -> io::Result<String> {
     let mut buffer = String::new();
     io::stdin().read_to_string(&mut buffer)?;
@@ -1,10 +1,17 @@
+use super::{
+    filters::{Filter, Filters},
+    NodeError,
+};
 use serde_json::Value;
+use std::fmt;
 use std::fs;
 use std::path::PathBuf;
 use std::slice::Iter;
+use std::sync::Arc;
 
 pub struct Context {
     payload: serde_json::Value,
+    filters: Arc<Filters>,
 }
 
 #[derive(Debug)]
@@ -19,10 +26,19 @@ pub enum Selector<'a> {
     Index(usize),
 }
 
+#[derive(Clone, Debug, PartialEq)]
+pub struct Interpolation<'a> {
+    pub selectors: Vec<Selector<'a>>,
+    pub filters: Vec<Filter<'a>>,
+}
+
 impl Context {
     pub fn empty() -> Self {
         let payload = serde_json::Value::Null;
-        Context { payload }
+        Context {
+            payload,
+            filters: Arc::default(),
+        }
     }
 
     pub fn from_file(file_path: PathBuf) -> Result<Self, ContextError> {
@@ -33,17 +49,64 @@ impl Context {
 
     pub fn load(input: &str) -> Result<Self, ContextError> {
         let payload = serde_json::from_str(input).map_err(ContextError::JsonError)?;
-        Ok(Context { payload })
+        Ok(Context {
+            payload,
+            filters: Arc::default(),
+        })
+    }
+
+    pub fn with_filters(mut self, filters: Arc<Filters>) -> Self {
+        self.filters = filters;
+        self
+    }
+
+    pub fn filters(&self) -> Arc<Filters> {
+        Arc::clone(&self.filters)
+    }
+
+    pub fn register_filter<F>(&mut self, name: &str, filter: F)
+    where
+        F: Fn(&Value, &[Value]) -> Result<Value, String> + Send + Sync + 'static,
+    {
+        Arc::make_mut(&mut self.filters).insert(name, filter);
     }
 
-    pub fn interpret(&self, value: &[Selector]) -> Option<String> {
-        self.at(value).map(value_to_string)
+    pub fn interpret<'a>(
+        &self,
+        interpolation: &Interpolation<'a>,
+    ) -> Result<String, NodeError<'a>> {
+        let value = self.evaluate(interpolation)?;
+
+        value_to_string(&value)
+            .ok_or_else(|| NodeError::JSONValueNotTextAtSelector(interpolation.selectors.to_vec()))
+    }
+
+    /// A missing value is an error unless the pipeline supplies a `default`, in which case the
+    /// filters see `null`.
+    pub fn evaluate<'a>(&self, interpolation: &Interpolation<'a>) -> Result<Value, NodeError<'a>> {
+        let Interpolation { selectors, filters } = interpolation;
+        let value = match self.at(selectors) {
+            Some(value) => value.clone(),
+            None if filters.iter().any(|f| f.name == "default") => Value::Null,
+            None => return Err(NodeError::JSONValueMissingAtSelector(selectors.to_vec())),
+        };
+
+        filters
+            .iter()
+            .try_fold(value, |value, filter| self.filters.apply(filter, &value))
     }
 
     pub fn at(&self, value: &[Selector]) -> Option<&Value> {
         handle(&self.payload, value.iter())
     }
 
+    pub fn scoped(&self, payload: Value) -> Self {
+        Context {
+            payload,
+            filters: self.filters(),
+        }
+    }
+
     pub fn extend(&self, key: &str, value: &Value) -> Self {
         match &self.payload {
             Value::Object(map) => {
@@ -51,15 +114,68 @@ impl Context {
                 new_map.insert(key.into(), value.clone());
                 Context {
                     payload: Value::Object(new_map),
+                    filters: self.filters(),
                 }
             }
             payload => Context {
                 payload: payload.clone(),
+                filters: self.filters(),
             },
         }
     }
 }
 
+/// Conditions treat `null`, `false`, `0`, `""`, `[]` and `{}` as false and every other value as
+/// true.
+pub fn is_truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(v) => *v,
+        Value::Number(n) => n.as_f64() != Some(0.0),
+        Value::String(s) => !s.is_empty(),
+        Value::Array(values) => !values.is_empty(),
+        Value::Object(values) => !values.is_empty(),
+    }
+}
+
+pub fn selector_path(selectors: &[Selector]) -> String {
+    selectors
+        .iter()
+        .enumerate()
+        .map(|(i, selector)| match selector {
+            Selector::Key(key) if i == 0 => key.to_string(),
+            selector => selector.to_string(),
+        })
+        .collect()
+}
+
+impl<'a> From<Vec<Selector<'a>>> for Interpolation<'a> {
+    fn from(selectors: Vec<Selector<'a>>) -> Self {
+        Interpolation {
+            selectors,
+            filters: vec![],
+        }
+    }
+}
+
+impl fmt::Display for ContextError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ContextError::JsonError(e) => write!(f, "invalid context JSON: {}", e),
+            ContextError::ContextIOError(e) => write!(f, "unable to read context: {}", e),
+        }
+    }
+}
+
+impl<'a> fmt::Display for Selector<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Selector::Key(key) => write!(f, ".{}", key),
+            Selector::Index(index) => write!(f, "[{}]", index),
+        }
+    }
+}
+
 fn handle<'a>(payload: &'a Value, mut value: Iter<Selector>) -> Option<&'a Value> {
     match value.next() {
         None => Some(payload),
@@ -79,14 +195,26 @@ fn handle<'a>(payload: &'a Value, mut value: Iter<Selector>) -> Option<&'a Value
     }
 }
 
-fn value_to_string(value: &Value) -> String {
+/// Scalars render as text and arrays of scalars render joined with `, `; objects and nested
+/// arrays have no text form.
+pub fn value_to_string(value: &Value) -> Option<String> {
+    match value {
+        Value::Array(values) => values
+            .iter()
+            .map(scalar_to_string)
+            .collect::<Option<Vec<_>>>()
+            .map(|values| values.join(", ")),
+        value => scalar_to_string(value),
+    }
+}
+
+fn scalar_to_string(value: &Value) -> Option<String> {
     match value {
-        Value::Null => String::new(),
-        Value::Bool(true) => "true".to_string(),
-        Value::Bool(false) => "false".to_string(),
-        Value::Number(n) => format!("{}", n),
-        Value::String(s) => s.to_string(),
-        Value::Array(_) => "array".to_string(),
-        Value::Object(_) => "object".to_string(),
+        Value::Null => Some(String::new()),
+        Value::Bool(true) => Some("true".to_string()),
+        Value::Bool(false) => Some("false".to_string()),
+        Value::Number(n) => Some(format!("{}", n)),
+        Value::String(s) => Some(s.to_string()),
+        Value::Array(_) | Value::Object(_) => None,
     }
 }
@@ -0,0 +1,170 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub path: Option<PathBuf>,
+    pub line: usize,
+    pub column: usize,
+    pub source_line: String,
+    pub message: String,
+}
+
+impl Diagnostic {
+    pub fn new(source: &str, remaining: &str) -> Self {
+        let offset = source.len() - remaining.trim_start_matches('\n').len();
+        let line_start = line_start(source, offset);
+        let source_line = &source[line_start..line_end(source, offset)];
+        let previous_line = source[..line_start]
+            .lines()
+            .rev()
+            .find(|l| !l.trim().is_empty());
+
+        Self::at(
+            Severity::Error,
+            source,
+            offset,
+            expected(source_line, previous_line),
+        )
+    }
+
+    pub fn warning(source: &str, fragment: &str, message: String) -> Self {
+        let offset = fragment.as_ptr() as usize - source.as_ptr() as usize;
+
+        Self::at(Severity::Warning, source, offset, message)
+    }
+
+    pub fn from_nom(source: &str, error: nom::Err<nom::error::Error<&str>>) -> Self {
+        match error {
+            nom::Err::Error(e) | nom::Err::Failure(e) => Self::new(source, e.input),
+            nom::Err::Incomplete(_) => Self::new(source, ""),
+        }
+    }
+
+    pub fn with_path<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.path = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    fn at(severity: Severity, source: &str, offset: usize, message: String) -> Self {
+        let line_start = line_start(source, offset);
+
+        Diagnostic {
+            severity,
+            path: None,
+            line: source[..line_start].matches('\n').count() + 1,
+            column: source[line_start..offset].chars().count() + 1,
+            source_line: source[line_start..line_end(source, offset)].to_string(),
+            message,
+        }
+    }
+}
+
+fn line_start(source: &str, offset: usize) -> usize {
+    source[..offset].rfind('\n').map_or(0, |i| i + 1)
+}
+
+fn line_end(source: &str, offset: usize) -> usize {
+    source[offset..]
+        .find('\n')
+        .map_or(source.len(), |i| offset + i)
+}
+
+fn expected(line: &str, previous_line: Option<&str>) -> String {
+    let indentation = &line[..line.len() - line.trim_start().len()];
+    let previous_indentation = previous_line.map_or(0, |l| l.len() - l.trim_start().len());
+
+    if indentation.contains('\t') {
+        "expected 2-space indentation, found a tab".to_string()
+    } else if !indentation.len().is_multiple_of(2) {
+        "expected 2-space indentation".to_string()
+    } else if indentation.len() > previous_indentation + 2 {
+        format!(
+            "expected at most {} spaces of indentation",
+            previous_indentation + 2
+        )
+    } else if line.starts_with("- extends ") {
+        "`- extends` must be the first line, the layout provides `!HTML`".to_string()
+    } else if !line.matches('"').count().is_multiple_of(2) {
+        "unterminated attribute quote".to_string()
+    } else if line.matches('(').count() > line.matches(')').count() {
+        "unterminated attribute list, expected `)`".to_string()
+    } else if line.matches('{').count() > line.matches('}').count() {
+        "unterminated interpolation, expected `}`".to_string()
+    } else {
+        "unexpected input".to_string()
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let path = self
+            .path
+            .as_ref()
+            .map_or("<input>".to_string(), |p| p.display().to_string());
+        let gutter = " ".repeat(self.line.to_string().len());
+
+        writeln!(f, "{}: {}", self.severity, self.message)?;
+        writeln!(f, "{}--> {}:{}:{}", gutter, path, self.line, self.column)?;
+        writeln!(f, "{} |", gutter)?;
+        writeln!(f, "{} | {}", self.line, self.source_line)?;
+        write!(f, "{} | {}^", gutter, " ".repeat(self.column - 1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Diagnostic;
+
+    #[test]
+    fn position_and_excerpt() {
+        let source = "%ul\n  %li(class=\"first) One\n  %li Two";
+        let diagnostic = Diagnostic::new(source, &source[10..]);
+
+        assert_eq!(diagnostic.line, 2);
+        assert_eq!(diagnostic.column, 7);
+        assert_eq!(diagnostic.source_line, "  %li(class=\"first) One");
+        assert_eq!(diagnostic.message, "unterminated attribute quote");
+        assert_eq!(
+            diagnostic.with_path("list.skt").to_string(),
+            "error: unterminated attribute quote\n --> list.skt:2:7\n  |\n2 |   %li(class=\"first) One\n  |       ^"
+        );
+    }
+
+    #[test]
+    fn indentation() {
+        let source = "%ul\n   %li One";
+        assert_eq!(
+            Diagnostic::new(source, &source[3..]).message,
+            "expected 2-space indentation"
+        );
+
+        let source = "%ul\n\t%li One";
+        assert_eq!(
+            Diagnostic::new(source, &source[3..]).message,
+            "expected 2-space indentation, found a tab"
+        );
+
+        let source = "%ul\n    %li One";
+        assert_eq!(
+            Diagnostic::new(source, &source[3..]).message,
+            "expected at most 2 spaces of indentation"
+        );
+    }
+}
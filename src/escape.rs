@@ -0,0 +1,43 @@
+pub fn text(value: &str) -> String {
+    escape(value, false)
+}
+
+pub fn attribute_value(value: &str) -> String {
+    escape(value, true)
+}
+
+fn escape(value: &str, within_attribute: bool) -> String {
+    let mut result = String::with_capacity(value.len());
+
+    for c in value.chars() {
+        match c {
+            '&' => result.push_str("&amp;"),
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '"' if within_attribute => result.push_str("&quot;"),
+            '\'' if within_attribute => result.push_str("&#39;"),
+            _ => result.push(c),
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn text_escaping() {
+        assert_eq!(
+            super::text("<b>Tom & \"Jerry\"</b>"),
+            "&lt;b&gt;Tom &amp; \"Jerry\"&lt;/b&gt;"
+        );
+    }
+
+    #[test]
+    fn attribute_value_escaping() {
+        assert_eq!(
+            super::attribute_value("\" onclick='alert(1)' data-x=\"<&>"),
+            "&quot; onclick=&#39;alert(1)&#39; data-x=&quot;&lt;&amp;&gt;"
+        );
+    }
+}
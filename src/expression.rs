@@ -0,0 +1,118 @@
+use super::{
+    context::{is_truthy, Context, Selector},
+    NodeError,
+};
+use serde_json::Value;
+use std::borrow::Cow;
+use std::cmp::Ordering;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expression<'a> {
+    Selector(Vec<Selector<'a>>),
+    Literal(Value),
+    Not(Box<Expression<'a>>),
+    And(Box<Expression<'a>>, Box<Expression<'a>>),
+    Or(Box<Expression<'a>>, Box<Expression<'a>>),
+    Comparison(Box<Expression<'a>>, Comparator, Box<Expression<'a>>),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Comparator {
+    Equal,
+    NotEqual,
+    LessThan,
+    LessThanOrEqual,
+    GreaterThan,
+    GreaterThanOrEqual,
+}
+
+impl<'a> Expression<'a> {
+    /// Missing values are falsy, and are returned as warnings alongside the result.
+    pub fn evaluate(&self, context: &Context) -> (bool, Vec<NodeError<'a>>) {
+        let mut errors = vec![];
+        let result = self.truthy(context, &mut errors);
+
+        (result, errors)
+    }
+
+    fn truthy(&self, context: &Context, errors: &mut Vec<NodeError<'a>>) -> bool {
+        match self {
+            Expression::Not(expression) => !expression.truthy(context, errors),
+            Expression::And(left, right) => {
+                left.truthy(context, errors) && right.truthy(context, errors)
+            }
+            Expression::Or(left, right) => {
+                left.truthy(context, errors) || right.truthy(context, errors)
+            }
+            Expression::Comparison(left, comparator, right) => {
+                comparator.compare(&left.value(context, errors), &right.value(context, errors))
+            }
+            Expression::Selector(_) | Expression::Literal(_) => {
+                is_truthy(&self.value(context, errors))
+            }
+        }
+    }
+
+    fn value<'c>(&self, context: &'c Context, errors: &mut Vec<NodeError<'a>>) -> Cow<'c, Value> {
+        match self {
+            Expression::Selector(selectors) => match context.at(selectors) {
+                Some(value) => Cow::Borrowed(value),
+                None => match length_at(context, selectors) {
+                    Some(length) => Cow::Owned(Value::from(length)),
+                    None => {
+                        errors.push(NodeError::JSONValueMissingAtSelector(selectors.to_vec()));
+                        Cow::Owned(Value::Null)
+                    }
+                },
+            },
+            Expression::Literal(value) => Cow::Owned(value.clone()),
+            expression => Cow::Owned(Value::Bool(expression.truthy(context, errors))),
+        }
+    }
+}
+
+fn length_at(context: &Context, selectors: &[Selector]) -> Option<usize> {
+    match selectors.split_last() {
+        Some((Selector::Key("length"), collection)) => match context.at(collection)? {
+            Value::Array(values) => Some(values.len()),
+            Value::Object(values) => Some(values.len()),
+            Value::String(value) => Some(value.chars().count()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+impl Comparator {
+    fn compare(&self, left: &Value, right: &Value) -> bool {
+        match self {
+            Comparator::Equal => equal(left, right),
+            Comparator::NotEqual => !equal(left, right),
+            Comparator::LessThan => order(left, right) == Some(Ordering::Less),
+            Comparator::LessThanOrEqual => matches!(
+                order(left, right),
+                Some(Ordering::Less) | Some(Ordering::Equal)
+            ),
+            Comparator::GreaterThan => order(left, right) == Some(Ordering::Greater),
+            Comparator::GreaterThanOrEqual => matches!(
+                order(left, right),
+                Some(Ordering::Greater) | Some(Ordering::Equal)
+            ),
+        }
+    }
+}
+
+fn equal(left: &Value, right: &Value) -> bool {
+    match (left, right) {
+        (Value::Number(l), Value::Number(r)) => l.as_f64() == r.as_f64(),
+        (l, r) => l == r,
+    }
+}
+
+fn order(left: &Value, right: &Value) -> Option<Ordering> {
+    match (left, right) {
+        (Value::Number(l), Value::Number(r)) => l.as_f64()?.partial_cmp(&r.as_f64()?),
+        (Value::String(l), Value::String(r)) => Some(l.cmp(r)),
+        _ => None,
+    }
+}
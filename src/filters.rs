@@ -0,0 +1,427 @@
+use super::{context::value_to_string, NodeError};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+pub type FilterFn = Arc<dyn Fn(&Value, &[Value]) -> Result<Value, String> + Send + Sync>;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Filter<'a> {
+    pub name: &'a str,
+    pub arguments: Vec<Value>,
+}
+
+#[derive(Clone)]
+pub struct Filters {
+    filters: HashMap<String, FilterFn>,
+}
+
+impl Filters {
+    pub fn insert<F>(&mut self, name: &str, filter: F)
+    where
+        F: Fn(&Value, &[Value]) -> Result<Value, String> + Send + Sync + 'static,
+    {
+        self.filters.insert(name.to_string(), Arc::new(filter));
+    }
+
+    pub fn apply<'a>(&self, filter: &Filter<'a>, value: &Value) -> Result<Value, NodeError<'a>> {
+        let function = self
+            .filters
+            .get(filter.name)
+            .ok_or(NodeError::UnknownFilter(filter.name))?;
+
+        function(value, &filter.arguments)
+            .map_err(|message| NodeError::FilterFailed(filter.name, message))
+    }
+}
+
+impl Default for Filters {
+    fn default() -> Self {
+        let mut filters = Filters {
+            filters: HashMap::new(),
+        };
+
+        filters.insert("upcase", |value, _| {
+            Ok(Value::from(text(value)?.to_uppercase()))
+        });
+        filters.insert("downcase", |value, _| {
+            Ok(Value::from(text(value)?.to_lowercase()))
+        });
+        filters.insert("number", number);
+        filters.insert("truncate", truncate);
+        filters.insert("join", join);
+        filters.insert("date", date);
+        filters.insert("json", |value, _| Ok(Value::from(value.to_string())));
+        filters.insert("default", |value, arguments| match value {
+            Value::Null | Value::Bool(false) => Ok(argument(arguments, 0)?.clone()),
+            Value::String(s) if s.is_empty() => Ok(argument(arguments, 0)?.clone()),
+            value => Ok(value.clone()),
+        });
+
+        filters
+    }
+}
+
+fn text(value: &Value) -> Result<String, String> {
+    value_to_string(value).ok_or_else(|| format!("expected text, found `{}`", value))
+}
+
+fn argument(arguments: &[Value], index: usize) -> Result<&Value, String> {
+    arguments
+        .get(index)
+        .ok_or_else(|| format!("expected at least {} argument(s)", index + 1))
+}
+
+fn usize_argument(arguments: &[Value], index: usize) -> Result<Option<usize>, String> {
+    match arguments.get(index) {
+        None => Ok(None),
+        Some(value) => value
+            .as_u64()
+            .map(|v| Some(v as usize))
+            .ok_or_else(|| format!("expected a whole number, found `{}`", value)),
+    }
+}
+
+fn number(value: &Value, arguments: &[Value]) -> Result<Value, String> {
+    let number = match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+    .ok_or_else(|| format!("expected a number, found `{}`", value))?;
+    let precision = usize_argument(arguments, 0)?.unwrap_or(0);
+
+    Ok(Value::from(format!("{:.*}", precision, number)))
+}
+
+fn truncate(value: &Value, arguments: &[Value]) -> Result<Value, String> {
+    let length = usize_argument(arguments, 0)?.ok_or("expected a length")?;
+    let text = text(value)?;
+
+    if text.chars().count() <= length {
+        Ok(Value::from(text))
+    } else {
+        let truncated: String = text.chars().take(length).collect();
+        Ok(Value::from(format!("{}...", truncated.trim_end())))
+    }
+}
+
+fn join(value: &Value, arguments: &[Value]) -> Result<Value, String> {
+    let separator = match arguments.first() {
+        Some(Value::String(separator)) => separator.as_str(),
+        Some(other) => return Err(format!("expected a separator, found `{}`", other)),
+        None => ", ",
+    };
+
+    match value {
+        Value::Array(values) => Ok(Value::from(
+            values
+                .iter()
+                .map(text)
+                .collect::<Result<Vec<_>, _>>()?
+                .join(separator),
+        )),
+        value => Ok(value.clone()),
+    }
+}
+
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+const WEEKDAYS: [&str; 7] = [
+    "Sunday",
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+];
+
+/// Dates are read from `YYYY-MM-DD[THH:MM[:SS]]` strings (any offset is ignored) or from Unix
+/// timestamps, and formatted with a subset of `strftime`: `%Y %y %m %d %e %H %M %S %B %b %A %a %%`.
+fn date(value: &Value, arguments: &[Value]) -> Result<Value, String> {
+    let format = match argument(arguments, 0)? {
+        Value::String(format) => format,
+        other => return Err(format!("expected a format, found `{}`", other)),
+    };
+    let moment = match value {
+        Value::Number(n) => n.as_i64().map(Moment::from_timestamp),
+        Value::String(s) => Moment::parse(s),
+        _ => None,
+    }
+    .ok_or_else(|| format!("expected a date, found `{}`", value))?;
+
+    Ok(Value::from(moment.format(format)))
+}
+
+struct Moment {
+    year: i64,
+    month: i64,
+    day: i64,
+    hour: i64,
+    minute: i64,
+    second: i64,
+}
+
+impl Moment {
+    fn from_timestamp(timestamp: i64) -> Self {
+        let days = timestamp.div_euclid(86_400);
+        let seconds = timestamp.rem_euclid(86_400);
+        let (year, month, day) = civil_from_days(days);
+
+        Moment {
+            year,
+            month,
+            day,
+            hour: seconds / 3600,
+            minute: seconds % 3600 / 60,
+            second: seconds % 60,
+        }
+    }
+
+    fn parse(input: &str) -> Option<Self> {
+        let number = |range: std::ops::Range<usize>| -> Option<i64> {
+            let digits = input.get(range)?;
+            if digits.chars().all(|c| c.is_ascii_digit()) {
+                digits.parse().ok()
+            } else {
+                None
+            }
+        };
+        let separator = |index: usize, expected: &[char]| {
+            input
+                .get(index..)
+                .and_then(|rest| rest.chars().next())
+                .is_some_and(|c| expected.contains(&c))
+        };
+
+        if !separator(4, &['-']) || !separator(7, &['-']) {
+            return None;
+        }
+
+        let (year, month, day) = (number(0..4)?, number(5..7)?, number(8..10)?);
+        if !(1..=12).contains(&month)
+            || !(1..=31).contains(&day)
+            || civil_from_days(days_from_civil(year, month, day)) != (year, month, day)
+        {
+            return None;
+        }
+
+        let (hour, minute, second) = if input.len() > 10 && separator(10, &['T', ' ']) {
+            let seconds = if input.len() > 16 && separator(16, &[':']) {
+                number(17..19)?
+            } else {
+                0
+            };
+            (number(11..13)?, number(14..16)?, seconds)
+        } else {
+            (0, 0, 0)
+        };
+
+        Some(Moment {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            second,
+        })
+    }
+
+    fn format(&self, format: &str) -> String {
+        let weekday = (days_from_civil(self.year, self.month, self.day) + 4).rem_euclid(7) as usize;
+        let month = MONTHS[self.month as usize - 1];
+        let mut output = String::new();
+        let mut chars = format.chars();
+
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                output.push(c);
+                continue;
+            }
+
+            match chars.next() {
+                Some('Y') => output.push_str(&self.year.to_string()),
+                Some('y') => output.push_str(&format!("{:02}", self.year.rem_euclid(100))),
+                Some('m') => output.push_str(&format!("{:02}", self.month)),
+                Some('d') => output.push_str(&format!("{:02}", self.day)),
+                Some('e') => output.push_str(&self.day.to_string()),
+                Some('H') => output.push_str(&format!("{:02}", self.hour)),
+                Some('M') => output.push_str(&format!("{:02}", self.minute)),
+                Some('S') => output.push_str(&format!("{:02}", self.second)),
+                Some('B') => output.push_str(month),
+                Some('b') => output.push_str(&month[..3]),
+                Some('A') => output.push_str(WEEKDAYS[weekday]),
+                Some('a') => output.push_str(&WEEKDAYS[weekday][..3]),
+                Some('%') => output.push('%'),
+                Some(other) => {
+                    output.push('%');
+                    output.push(other);
+                }
+                None => output.push('%'),
+            }
+        }
+
+        output
+    }
+}
+
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+
+    (year_of_era + era * 400 + (month <= 2) as i64, month, day)
+}
+
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * 146_097 + day_of_era - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Filter, Filters};
+    use serde_json::{json, Value};
+
+    fn apply(name: &str, value: Value, arguments: Vec<Value>) -> Value {
+        Filters::default()
+            .apply(&Filter { name, arguments }, &value)
+            .unwrap()
+    }
+
+    #[test]
+    fn text_filters() {
+        assert_eq!(apply("upcase", json!("Hello"), vec![]), json!("HELLO"));
+        assert_eq!(apply("downcase", json!("Hello"), vec![]), json!("hello"));
+        assert_eq!(
+            apply("truncate", json!("The quick brown fox"), vec![json!(9)]),
+            json!("The quick...")
+        );
+        assert_eq!(
+            apply("truncate", json!("Short"), vec![json!(9)]),
+            json!("Short")
+        );
+    }
+
+    #[test]
+    fn number_filter() {
+        assert_eq!(
+            apply("number", json!(1.23456), vec![json!(2)]),
+            json!("1.23")
+        );
+        assert_eq!(apply("number", json!("12"), vec![json!(1)]), json!("12.0"));
+        assert_eq!(apply("number", json!(2.5), vec![]), json!("2"));
+        assert!(Filters::default()
+            .apply(
+                &Filter {
+                    name: "number",
+                    arguments: vec![]
+                },
+                &json!("twelve")
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn join_and_default() {
+        assert_eq!(
+            apply("join", json!(["a", 1, true]), vec![json!(", ")]),
+            json!("a, 1, true")
+        );
+        assert_eq!(apply("join", json!(["a", "b"]), vec![]), json!("a, b"));
+        assert_eq!(
+            apply("default", Value::Null, vec![json!("n/a")]),
+            json!("n/a")
+        );
+        assert_eq!(apply("default", json!(0), vec![json!("n/a")]), json!(0));
+    }
+
+    #[test]
+    fn date_filter() {
+        assert_eq!(
+            apply(
+                "date",
+                json!("2024-02-29T13:05:09Z"),
+                vec![json!("%Y-%m-%d %H:%M:%S")]
+            ),
+            json!("2024-02-29 13:05:09")
+        );
+        assert_eq!(
+            apply("date", json!("2024-03-05"), vec![json!("%a, %e %B %Y")]),
+            json!("Tue, 5 March 2024")
+        );
+        assert_eq!(
+            apply("date", json!(0), vec![json!("%A %d %b %y")]),
+            json!("Thursday 01 Jan 70")
+        );
+        assert_eq!(
+            apply("date", json!(1_709_596_800), vec![json!("%Y-%m-%d")]),
+            json!("2024-03-05")
+        );
+        for impossible in &["2024-02-30", "2023-02-29", "2024-04-31"] {
+            assert!(Filters::default()
+                .apply(
+                    &Filter {
+                        name: "date",
+                        arguments: vec![json!("%Y-%m-%d")]
+                    },
+                    &json!(impossible)
+                )
+                .is_err());
+        }
+    }
+
+    #[test]
+    fn json_filter() {
+        assert_eq!(
+            apply("json", json!({"a": [1, "b"]}), vec![]),
+            json!("{\"a\":[1,\"b\"]}")
+        );
+    }
+
+    #[test]
+    fn unknown_filters() {
+        assert_eq!(
+            Filters::default()
+                .apply(
+                    &Filter {
+                        name: "shout",
+                        arguments: vec![]
+                    },
+                    &json!("hi")
+                )
+                .unwrap_err()
+                .to_string(),
+            "unknown filter `shout`"
+        );
+    }
+}
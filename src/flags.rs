@@ -12,4 +12,11 @@ pub struct Flags {
 
     #[structopt(long)]
     pub fragments: Option<PathBuf>,
+
+    /// Fail on render errors
+    ///
+    /// Exit with a non-zero status instead of printing HTML when any render error (missing
+    /// values, unknown fragments or blocks) occurs
+    #[structopt(long)]
+    pub strict: bool,
 }
@@ -1,47 +1,59 @@
-use super::{parser, Nodes};
+use super::{parser, Diagnostic, NodeError, Nodes};
 use std::collections::HashMap;
+use std::fmt;
 use std::fs;
 use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
 
-pub struct Fragments<'a>(HashMap<PathBuf, Result<Nodes<'a>, FragmentError<'a>>>);
+#[derive(Default)]
+pub struct Fragments<'a>(HashMap<PathBuf, Result<Nodes<'a>, FragmentError>>);
 
 impl<'a> Fragments<'a> {
-    pub fn get(&self, key: &PathBuf) -> Option<&Nodes<'a>> {
+    pub fn get(&self, key: &PathBuf) -> Result<&Nodes<'a>, NodeError<'a>> {
         match self.0.get(key) {
-            Some(Ok(v)) => Some(v),
-            _ => None,
+            Some(Ok(v)) => Ok(v),
+            Some(Err(e)) => Err(NodeError::FragmentParseError(e.diagnostic().clone())),
+            None => Err(NodeError::InvalidFragmentPath(key.to_path_buf())),
         }
     }
 
-    pub fn insert(
-        &mut self,
-        key: PathBuf,
-        value: Result<Nodes<'a>, FragmentError<'a>>,
-    ) -> &mut Self {
+    pub fn insert(&mut self, key: PathBuf, value: Result<Nodes<'a>, FragmentError>) -> &mut Self {
         self.0.insert(key, value);
         self
     }
 
-    pub fn parse(path: &PathBuf, input: &'a str) -> Result<Nodes<'a>, FragmentError<'a>> {
+    pub fn parse(path: &Path, input: &'a str) -> Result<Nodes<'a>, FragmentError> {
         match parser::parse(input) {
             Ok(("", n)) => Ok(n),
-            Ok(_) => Err(FragmentError::IncompleteParse(path.to_path_buf())),
-            Err(e) => Err(FragmentError::ParseError(e)),
+            Ok((rest, _)) => Err(FragmentError::IncompleteParse(
+                Diagnostic::new(input, rest).with_path(path),
+            )),
+            Err(e) => Err(FragmentError::ParseError(
+                Diagnostic::from_nom(input, e).with_path(path),
+            )),
         }
     }
 }
 
-impl<'a> std::default::Default for Fragments<'a> {
-    fn default() -> Self {
-        Fragments(HashMap::new())
+#[derive(Debug)]
+pub enum FragmentError {
+    IncompleteParse(Diagnostic),
+    ParseError(Diagnostic),
+}
+
+impl FragmentError {
+    pub fn diagnostic(&self) -> &Diagnostic {
+        match self {
+            FragmentError::IncompleteParse(diagnostic) => diagnostic,
+            FragmentError::ParseError(diagnostic) => diagnostic,
+        }
     }
 }
 
-#[derive(Debug)]
-pub enum FragmentError<'a> {
-    IncompleteParse(PathBuf),
-    ParseError(nom::Err<nom::error::Error<&'a str>>),
+impl fmt::Display for FragmentError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.diagnostic())
+    }
 }
 
 pub fn new(fragments_path: PathBuf) -> HashMap<PathBuf, String> {
@@ -3,6 +3,10 @@ mod attributes;
 mod builder;
 pub mod cli;
 pub mod context;
+mod diagnostic;
+mod escape;
+mod expression;
+mod filters;
 pub mod flags;
 mod fragments;
 mod node;
@@ -16,6 +20,9 @@ pub use crate::socket::*;
 pub use attribute::*;
 pub use attributes::*;
 pub use builder::*;
+pub use diagnostic::*;
+pub use expression::*;
+pub use filters::*;
 pub use fragments::*;
 pub use node::*;
 pub use nodes::*;
@@ -1,46 +1,205 @@
 use super::{
-    context::{Context, Selector},
-    Blocks, Builder, Fragments, Nodes, Tag,
+    context::{selector_path, Context, Interpolation, Selector},
+    escape, include, BlockScope, Blocks, Builder, Caller, Diagnostic, Expression, Fragments, Nodes,
+    Tag,
 };
 use pulldown_cmark::{html, Options, Parser};
-use serde_json::Value;
-use std::path::PathBuf;
+use serde_json::{json, Map, Value};
+use std::fmt;
+use std::path::{Path, PathBuf};
 
 pub enum Node<'a> {
     Text(&'a str),
     Markdown(Vec<&'a str>),
-    InterpolatedText(Vec<Selector<'a>>),
+    InterpolatedText(Interpolation<'a>),
+    RawInterpolatedText(Interpolation<'a>),
+    InterpolatedLine(Vec<Node<'a>>),
     BlockValue(&'a str),
     Element {
         tag: Tag<'a>,
         children: Nodes<'a>,
     },
     ForLoop {
+        key: Option<&'a str>,
         local: &'a str,
         selectors: Vec<Selector<'a>>,
         children: Nodes<'a>,
+        empty_children: Nodes<'a>,
     },
     IfElse {
-        selectors: Vec<Selector<'a>>,
-        true_children: Nodes<'a>,
+        branches: Vec<(Expression<'a>, Nodes<'a>)>,
         false_children: Nodes<'a>,
     },
     Fragment {
         path: PathBuf,
+        arguments: Vec<(&'a str, Argument<'a>)>,
+        slots: Blocks<'a>,
     },
     Block {
         name: &'a str,
         children: Nodes<'a>,
     },
+    Slot {
+        name: &'a str,
+        children: Nodes<'a>,
+    },
+    Let {
+        name: &'a str,
+        value: Interpolation<'a>,
+    },
+    Super,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Argument<'a> {
+    Literal(Value),
+    Interpolated(Interpolation<'a>),
 }
 
 #[derive(Debug)]
 pub enum NodeError<'a> {
     InvalidFragmentPath(PathBuf),
+    FragmentParseError(Diagnostic),
     InvalidBlockName(&'a str),
     JSONValueMissingAtSelector(Vec<Selector<'a>>),
     JSONValueNotArrayAtSelector(Vec<Selector<'a>>),
-    JSONValueNotBoolAtSelector(Vec<Selector<'a>>),
+    JSONValueNotTextAtSelector(Vec<Selector<'a>>),
+    VoidElementWithChildren(&'a str),
+    UnknownFilter(&'a str),
+    FilterFailed(&'a str, String),
+    SuperOutsideBlock,
+    FragmentCycle(Vec<PathBuf>),
+    BlockCycle(Vec<&'a str>),
+}
+
+fn loop_entries<'a, 'c>(
+    context: &'c Context,
+    selectors: &[Selector<'a>],
+    with_keys: bool,
+) -> Result<Vec<(Value, &'c Value)>, NodeError<'a>> {
+    match context.at(selectors) {
+        None => Err(NodeError::JSONValueMissingAtSelector(selectors.to_vec())),
+        Some(Value::Null) => Ok(vec![]),
+        Some(Value::Array(values)) => Ok(values
+            .iter()
+            .enumerate()
+            .map(|(index, value)| (Value::from(index), value))
+            .collect()),
+        Some(Value::Object(values)) if with_keys => Ok(values
+            .iter()
+            .map(|(key, value)| (Value::from(key.as_str()), value))
+            .collect()),
+        Some(_) => Err(NodeError::JSONValueNotArrayAtSelector(selectors.to_vec())),
+    }
+}
+
+/// Fragments called `with` arguments render against those arguments alone.
+fn fragment_context<'a>(
+    context: &Context,
+    arguments: &[(&'a str, Argument<'a>)],
+    builder: &mut Builder<String, NodeError<'a>>,
+) -> Option<Context> {
+    if arguments.is_empty() {
+        return None;
+    }
+
+    let mut payload = Map::new();
+    for (name, argument) in arguments {
+        match argument {
+            Argument::Literal(value) => {
+                payload.insert(name.to_string(), value.clone());
+            }
+            Argument::Interpolated(interpolation) => match context.evaluate(interpolation) {
+                Ok(value) => {
+                    payload.insert(name.to_string(), value);
+                }
+                Err(e) => builder.warn(e),
+            },
+        }
+    }
+
+    Some(context.scoped(Value::Object(payload)))
+}
+
+fn loop_metadata(index: usize, length: usize) -> Value {
+    json!({
+        "index": index + 1,
+        "index0": index,
+        "first": index == 0,
+        "last": index + 1 == length,
+        "length": length,
+    })
+}
+
+impl<'a> NodeError<'a> {
+    pub fn source_fragment(&self) -> Option<&'a str> {
+        match self {
+            NodeError::InvalidFragmentPath(_)
+            | NodeError::FragmentParseError(_)
+            | NodeError::SuperOutsideBlock
+            | NodeError::FragmentCycle(_) => None,
+            NodeError::InvalidBlockName(name) => Some(name),
+            NodeError::JSONValueMissingAtSelector(selectors)
+            | NodeError::JSONValueNotArrayAtSelector(selectors)
+            | NodeError::JSONValueNotTextAtSelector(selectors) => match selectors.first() {
+                Some(Selector::Key(key)) => Some(key),
+                _ => None,
+            },
+            NodeError::BlockCycle(names) => names.last().copied(),
+            NodeError::VoidElementWithChildren(name)
+            | NodeError::UnknownFilter(name)
+            | NodeError::FilterFailed(name, _) => Some(name),
+        }
+    }
+}
+
+impl<'a> fmt::Display for NodeError<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NodeError::InvalidFragmentPath(path) => {
+                write!(f, "unknown fragment `{}`", path.display())
+            }
+            NodeError::FragmentParseError(diagnostic) => write!(
+                f,
+                "invalid fragment `{}`: {}",
+                diagnostic
+                    .path
+                    .as_ref()
+                    .map_or("<input>".to_string(), |p| p.display().to_string()),
+                diagnostic.message
+            ),
+            NodeError::InvalidBlockName(name) => write!(f, "unknown block `{}`", name),
+            NodeError::JSONValueMissingAtSelector(selectors) => {
+                write!(f, "no value at `{}`", selector_path(selectors))
+            }
+            NodeError::JSONValueNotArrayAtSelector(selectors) => {
+                write!(f, "value at `{}` is not an array", selector_path(selectors))
+            }
+            NodeError::JSONValueNotTextAtSelector(selectors) => write!(
+                f,
+                "value at `{}` cannot be rendered as text, use the `json` filter",
+                selector_path(selectors)
+            ),
+            NodeError::VoidElementWithChildren(name) => {
+                write!(f, "void element `{}` cannot have children", name)
+            }
+            NodeError::UnknownFilter(name) => write!(f, "unknown filter `{}`", name),
+            NodeError::FilterFailed(name, message) => {
+                write!(f, "filter `{}` failed: {}", name, message)
+            }
+            NodeError::SuperOutsideBlock => write!(f, "`- super` used outside a block override"),
+            NodeError::FragmentCycle(paths) => write!(
+                f,
+                "fragment cycle: {}",
+                paths
+                    .iter()
+                    .map(|p| p.display().to_string())
+                    .collect::<Vec<_>>()
+                    .join(" -> ")
+            ),
+            NodeError::BlockCycle(names) => write!(f, "block cycle: {}", names.join(" -> ")),
+        }
+    }
 }
 
 impl<'a> Node<'a> {
@@ -49,8 +208,9 @@ impl<'a> Node<'a> {
         mut builder: Builder<String, NodeError<'a>>,
         context: &Context,
         fragments: &Fragments<'a>,
-        blocks: &Blocks<'a>,
-        styles: &'a Option<String>,
+        blocks: &BlockScope<'a, '_>,
+        styles: &Option<String>,
+        includes: &[&Path],
     ) -> Builder<String, NodeError<'a>> {
         match self {
             Node::Text(v) => builder.append(v.to_string()),
@@ -62,70 +222,166 @@ impl<'a> Node<'a> {
 
                 builder.append(html_output)
             }
-            Node::InterpolatedText(selectors) => match context.interpret(selectors) {
-                None => builder.warn(NodeError::JSONValueMissingAtSelector(selectors.to_vec())),
-                Some(value) => builder.append(value),
+            Node::InterpolatedText(interpolation) => match context.interpret(interpolation) {
+                Ok(value) => builder.append(escape::text(&value)),
+                Err(e) => builder.warn(e),
             },
-            Node::BlockValue(name) => {
-                if let Some(boxed_nodes) = blocks.get(name) {
-                    builder = boxed_nodes.to_html(builder, context, fragments, blocks, styles);
-                } else {
-                    builder.warn(NodeError::InvalidBlockName(name))
-                }
+            Node::RawInterpolatedText(interpolation) => match context.interpret(interpolation) {
+                Ok(value) => builder.append(value),
+                Err(e) => builder.warn(e),
+            },
+            Node::InterpolatedLine(segments) => {
+                builder = segments.iter().fold(builder, |acc, segment| {
+                    segment.to_html(acc, context, fragments, blocks, styles, includes)
+                })
             }
+            Node::BlockValue(name) => match blocks.find(name) {
+                Ok(Some((layer, nodes))) => {
+                    builder = match blocks.caller(layer) {
+                        Some(caller) => nodes.to_html(
+                            builder,
+                            caller.context,
+                            fragments,
+                            caller.blocks,
+                            styles,
+                            caller.includes,
+                        ),
+                        None => {
+                            let blocks = blocks.within(name, layer, None);
+                            nodes.to_html(builder, context, fragments, &blocks, styles, includes)
+                        }
+                    }
+                }
+                Ok(None) => builder.warn(NodeError::InvalidBlockName(name)),
+                Err(e) => builder.warn(e),
+            },
             Node::Element { tag, children } => {
-                builder.append(tag.open_tag_html(context));
-                builder = children.to_html(builder, context, fragments, blocks, styles);
+                let open_tag = tag.open_tag_html(context, &mut builder);
+                builder.append(open_tag);
+                if tag.is_void() {
+                    if !children.is_blank() {
+                        builder.warn(NodeError::VoidElementWithChildren(tag.name))
+                    }
+                } else {
+                    builder =
+                        children.to_html(builder, context, fragments, blocks, styles, includes);
+                }
                 builder.append(tag.additional_markup(styles));
                 builder.append(tag.close_tag_html());
             }
             Node::ForLoop {
+                key,
                 local,
                 selectors,
                 children,
-            } => match context.at(selectors) {
-                None => builder.warn(NodeError::JSONValueMissingAtSelector(selectors.to_vec())),
-                Some(Value::Array(loopable)) => {
-                    builder = loopable.iter().fold(builder, |acc, looped_value| {
-                        children.to_html(
-                            acc,
-                            &context.extend(local, looped_value),
-                            fragments,
-                            blocks,
-                            styles,
-                        )
-                    })
+                empty_children,
+            } => match loop_entries(context, selectors, key.is_some()) {
+                Err(NodeError::JSONValueMissingAtSelector(_)) if !empty_children.is_empty() => {
+                    builder = empty_children
+                        .to_html(builder, context, fragments, blocks, styles, includes)
+                }
+                Err(e) => builder.warn(e),
+                Ok(entries) if entries.is_empty() => {
+                    builder = empty_children
+                        .to_html(builder, context, fragments, blocks, styles, includes)
+                }
+                Ok(entries) => {
+                    let length = entries.len();
+                    builder = entries.iter().enumerate().fold(
+                        builder,
+                        |acc, (index, (entry_key, value))| {
+                            let mut looped_context = context
+                                .extend(local, value)
+                                .extend("loop", &loop_metadata(index, length));
+                            if let Some(key) = key {
+                                looped_context = looped_context.extend(key, entry_key);
+                            }
+
+                            children.to_html(
+                                acc,
+                                &looped_context,
+                                fragments,
+                                blocks,
+                                styles,
+                                includes,
+                            )
+                        },
+                    )
                 }
-                Some(_) => builder.warn(NodeError::JSONValueNotArrayAtSelector(selectors.to_vec())),
             },
             Node::IfElse {
-                selectors,
-                true_children,
+                branches,
                 false_children,
-            } => match context.at(selectors) {
-                None => builder.warn(NodeError::JSONValueMissingAtSelector(selectors.to_vec())),
-                Some(Value::Bool(true)) => {
-                    builder = true_children.to_html(builder, context, fragments, blocks, styles)
+            } => {
+                let children = branches
+                    .iter()
+                    .find(|(condition, _)| {
+                        let (truthy, errors) = condition.evaluate(context);
+                        errors.into_iter().for_each(|e| builder.warn(e));
+                        truthy
+                    })
+                    .map_or(false_children, |(_, children)| children);
+                builder = children.to_html(builder, context, fragments, blocks, styles, includes)
+            }
+            Node::Fragment {
+                path,
+                arguments,
+                slots,
+            } => match fragments.get(path) {
+                Ok(nodes) => match include(includes, path) {
+                    Ok(nested) => {
+                        let scoped = fragment_context(context, arguments, &mut builder);
+                        let caller = Caller {
+                            context,
+                            blocks,
+                            includes,
+                        };
+                        let context = scoped.as_ref().unwrap_or(context);
+                        let blocks = if slots.is_empty() {
+                            BlockScope::default()
+                        } else {
+                            BlockScope::slots(slots, &caller)
+                        };
+                        builder =
+                            nodes.to_html(builder, context, fragments, &blocks, styles, &nested)
+                    }
+                    Err(e) => builder.warn(e),
+                },
+                Err(e) => builder.warn(e),
+            },
+            Node::Block { name, children } | Node::Slot { name, children } => match blocks
+                .find(name)
+            {
+                Ok(Some((layer, nodes))) => {
+                    builder = match blocks.caller(layer) {
+                        Some(caller) => nodes.to_html(
+                            builder,
+                            caller.context,
+                            fragments,
+                            caller.blocks,
+                            styles,
+                            caller.includes,
+                        ),
+                        None => {
+                            let blocks = blocks.within(name, layer, Some(children));
+                            nodes.to_html(builder, context, fragments, &blocks, styles, includes)
+                        }
+                    }
                 }
-                Some(Value::Bool(false)) => {
-                    builder = false_children.to_html(builder, context, fragments, blocks, styles)
+                Ok(None) => {
+                    builder =
+                        children.to_html(builder, context, fragments, blocks, styles, includes)
                 }
-                Some(_) => builder.warn(NodeError::JSONValueNotBoolAtSelector(selectors.to_vec())),
+                Err(e) => builder.warn(e),
             },
-            Node::Fragment { path } => {
-                if let Some(nodes) = fragments.get(path) {
-                    builder = nodes.to_html(builder, context, fragments, blocks, styles)
-                } else {
-                    builder.warn(NodeError::InvalidFragmentPath(path.to_path_buf()))
-                }
-            }
-            Node::Block { name, children } => {
-                if let Some(boxed_nodes) = blocks.get(name) {
-                    builder = boxed_nodes.to_html(builder, context, fragments, blocks, styles)
-                } else {
-                    builder = children.to_html(builder, context, fragments, blocks, styles)
+            Node::Super => match blocks.parent() {
+                Some((blocks, Some(nodes))) => {
+                    builder = nodes.to_html(builder, context, fragments, &blocks, styles, includes)
                 }
-            }
+                Some((_, None)) => (),
+                None => builder.warn(NodeError::SuperOutsideBlock),
+            },
+            Node::Let { .. } => (),
         };
 
         builder
@@ -1,9 +1,170 @@
 use super::{context::Context, Builder, Fragments, Node, NodeError};
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 pub type Blocks<'a> = HashMap<&'a str, Box<Nodes<'a>>>;
 
+/// Pushes a fragment or layout onto the include stack, failing with the cycle it would close when
+/// it is already being rendered.
+pub fn include<'p, 'a>(
+    includes: &[&'p Path],
+    path: &'p Path,
+) -> Result<Vec<&'p Path>, NodeError<'a>> {
+    match includes.iter().position(|included| *included == path) {
+        Some(start) => Err(NodeError::FragmentCycle(
+            includes[start..]
+                .iter()
+                .chain(std::iter::once(&path))
+                .map(|p| p.to_path_buf())
+                .collect(),
+        )),
+        None => {
+            let mut includes = includes.to_vec();
+            includes.push(path);
+            Ok(includes)
+        }
+    }
+}
+
+/// The block overrides visible while rendering, ordered from the most derived template to its
+/// base layout, along with the block whose override is being rendered so `- super` can continue
+/// down the chain, and every override entered on the way so blocks cannot include each other.
+#[derive(Clone, Default)]
+pub struct BlockScope<'a, 'b> {
+    layers: Vec<Layer<'a, 'b>>,
+    current: Option<Override<'a, 'b>>,
+    active: Vec<(&'a str, usize)>,
+}
+
+#[derive(Clone, Copy)]
+struct Layer<'a, 'b> {
+    blocks: &'b Blocks<'a>,
+    caller: Option<&'b Caller<'a, 'b>>,
+}
+
+/// Where a fragment was called from. Slot content renders there rather than inside the fragment,
+/// so it sees the caller's variables and slots, and nesting a fragment inside itself is not a
+/// cycle.
+pub struct Caller<'a, 'b> {
+    pub context: &'b Context,
+    pub blocks: &'b BlockScope<'a, 'b>,
+    pub includes: &'b [&'b Path],
+}
+
+#[derive(Clone, Copy)]
+struct Override<'a, 'b> {
+    name: &'a str,
+    next_layer: usize,
+    default: Option<&'b Nodes<'a>>,
+}
+
+impl<'a, 'b> BlockScope<'a, 'b> {
+    pub fn slots(slots: &'b Blocks<'a>, caller: &'b Caller<'a, 'b>) -> Self {
+        BlockScope {
+            layers: vec![Layer {
+                blocks: slots,
+                caller: Some(caller),
+            }],
+            current: None,
+            active: vec![],
+        }
+    }
+
+    /// Adds a layout's own blocks beneath the overrides already in scope.
+    pub fn inherit(&self, blocks: &'b Blocks<'a>) -> Self {
+        let mut layers = self.layers.clone();
+        layers.push(Layer {
+            blocks,
+            caller: None,
+        });
+
+        BlockScope {
+            layers,
+            current: None,
+            active: self.active.clone(),
+        }
+    }
+
+    /// The override to render for a block. A block nested inside its own override resolves to
+    /// the next layer down, and overrides that would include each other fail with the cycle.
+    pub fn find(&self, name: &'a str) -> Result<Option<(usize, &'b Nodes<'a>)>, NodeError<'a>> {
+        let start = match self.current {
+            Some(current) if current.name == name => current.next_layer,
+            _ => 0,
+        };
+
+        match self.find_from(name, start) {
+            Some((layer, nodes)) => match self
+                .active
+                .iter()
+                .position(|&(active, active_layer)| active == name && active_layer == layer)
+            {
+                Some(start) => Err(NodeError::BlockCycle(
+                    self.active[start..]
+                        .iter()
+                        .map(|&(name, _)| name)
+                        .chain(std::iter::once(name))
+                        .collect(),
+                )),
+                None => Ok(Some((layer, nodes))),
+            },
+            None => Ok(None),
+        }
+    }
+
+    /// The fragment call that supplied a layer's blocks as slot content.
+    pub fn caller(&self, layer: usize) -> Option<&'b Caller<'a, 'b>> {
+        self.layers.get(layer).and_then(|layer| layer.caller)
+    }
+
+    /// The definition `- super` renders inside the current override: the next layer's override,
+    /// or the block's default content.
+    pub fn parent(&self) -> Option<(BlockScope<'a, 'b>, Option<&'b Nodes<'a>>)> {
+        let current = self.current?;
+
+        match self.find_from(current.name, current.next_layer) {
+            Some((layer, nodes)) => Some((
+                self.within(current.name, layer, current.default),
+                Some(nodes),
+            )),
+            None => Some((
+                BlockScope {
+                    layers: self.layers.clone(),
+                    current: None,
+                    active: self.active.clone(),
+                },
+                current.default,
+            )),
+        }
+    }
+
+    pub fn within(&self, name: &'a str, layer: usize, default: Option<&'b Nodes<'a>>) -> Self {
+        let mut active = self.active.clone();
+        active.push((name, layer));
+
+        BlockScope {
+            layers: self.layers.clone(),
+            current: Some(Override {
+                name,
+                next_layer: layer + 1,
+                default,
+            }),
+            active,
+        }
+    }
+
+    fn find_from(&self, name: &str, start: usize) -> Option<(usize, &'b Nodes<'a>)> {
+        self.layers
+            .iter()
+            .enumerate()
+            .skip(start)
+            .find_map(|(index, layer)| layer.blocks.get(name).map(|nodes| (index, nodes.as_ref())))
+    }
+}
+
+/// The block name `- yield` renders: a fragment call's children outside any `- block`.
+pub const DEFAULT_SLOT: &str = "yield";
+
 pub enum Nodes<'a> {
     Fragment { nodes: Vec<Node<'a>> },
     Document { nodes: Vec<Node<'a>> },
@@ -24,24 +185,36 @@ impl<'a> Nodes<'a> {
         mut builder: Builder<String, NodeError<'a>>,
         context: &Context,
         fragments: &Fragments<'a>,
-        blocks: &Blocks<'a>,
-        styles: &'a Option<String>,
+        blocks: &BlockScope<'a, '_>,
+        styles: &Option<String>,
+        includes: &[&Path],
     ) -> Builder<String, NodeError<'a>> {
         match self {
             Nodes::Fragment { nodes } => {
-                builder = Self::nodes_to_html(builder, nodes, context, fragments, blocks, styles);
+                builder = Self::nodes_to_html(
+                    builder, nodes, context, fragments, blocks, styles, includes,
+                );
             }
             Nodes::Document { nodes } => {
                 builder.append("<!DOCTYPE html>".to_string());
-                builder = Self::nodes_to_html(builder, nodes, context, fragments, blocks, styles);
-            }
-            Nodes::FragmentSubclass { layout, blocks } => {
-                if let Some(nodes) = fragments.get(layout) {
-                    builder = nodes.to_html(builder, context, fragments, blocks, styles)
-                } else {
-                    builder.warn(NodeError::InvalidFragmentPath(layout.to_path_buf()))
-                }
+                builder = Self::nodes_to_html(
+                    builder, nodes, context, fragments, blocks, styles, includes,
+                );
             }
+            Nodes::FragmentSubclass {
+                layout,
+                blocks: own_blocks,
+            } => match fragments.get(layout) {
+                Ok(nodes) => match include(includes, layout) {
+                    Ok(includes) => {
+                        let blocks = blocks.inherit(own_blocks);
+                        builder =
+                            nodes.to_html(builder, context, fragments, &blocks, styles, &includes)
+                    }
+                    Err(e) => builder.warn(e),
+                },
+                Err(e) => builder.warn(e),
+            },
         }
 
         builder
@@ -55,6 +228,24 @@ impl<'a> Nodes<'a> {
         }
     }
 
+    pub fn is_empty(&self) -> bool {
+        match self {
+            Nodes::Fragment { nodes } => nodes.is_empty(),
+            Nodes::Document { nodes } => nodes.is_empty(),
+            Nodes::FragmentSubclass { .. } => false,
+        }
+    }
+
+    /// Whether the nodes hold nothing but whitespace, such as the text after `%br `.
+    pub fn is_blank(&self) -> bool {
+        match self {
+            Nodes::Fragment { nodes } | Nodes::Document { nodes } => nodes
+                .iter()
+                .all(|node| matches!(node, Node::Text(text) if text.trim().is_empty())),
+            Nodes::FragmentSubclass { .. } => false,
+        }
+    }
+
     pub fn new_fragment(nodes: Vec<Node<'a>>) -> Self {
         Nodes::Fragment { nodes }
     }
@@ -74,16 +265,58 @@ impl<'a> Nodes<'a> {
         Nodes::FragmentSubclass { layout, blocks }
     }
 
+    /// Splits a fragment call's children into its `- block` slots, with everything else going to
+    /// `- yield`. `- slot` and `- yield` points stay in that content, so a component can pass its
+    /// own slots on to the fragments it calls.
+    pub fn into_slots(self) -> Blocks<'a> {
+        let nodes = match self {
+            Nodes::Fragment { nodes } | Nodes::Document { nodes } => nodes,
+            Nodes::FragmentSubclass { blocks, .. } => return blocks,
+        };
+        let mut slots = HashMap::new();
+        let mut default_slot = vec![];
+        for node in nodes {
+            match node {
+                Node::Block { name, children } => {
+                    slots.insert(name, Box::new(children));
+                }
+                node => default_slot.push(node),
+            }
+        }
+
+        if !default_slot.is_empty() {
+            slots.insert(DEFAULT_SLOT, Box::new(Nodes::new_fragment(default_slot)));
+        }
+
+        slots
+    }
+
+    /// `- let` bindings extend the context for the siblings that follow them.
     fn nodes_to_html(
-        builder: Builder<String, NodeError<'a>>,
+        mut builder: Builder<String, NodeError<'a>>,
         nodes: &[Node<'a>],
         context: &Context,
         fragments: &Fragments<'a>,
-        blocks: &Blocks<'a>,
-        styles: &'a Option<String>,
+        blocks: &BlockScope<'a, '_>,
+        styles: &Option<String>,
+        includes: &[&Path],
     ) -> Builder<String, NodeError<'a>> {
-        nodes.iter().fold(builder, |acc, n| {
-            n.to_html(acc, context, fragments, blocks, styles)
-        })
+        let mut scoped: Option<Context> = None;
+
+        for node in nodes {
+            let current = scoped.as_ref().unwrap_or(context);
+
+            match node {
+                Node::Let { name, value } => match current.evaluate(value) {
+                    Ok(value) => scoped = Some(current.extend(name, &value)),
+                    Err(e) => builder.warn(e),
+                },
+                node => {
+                    builder = node.to_html(builder, current, fragments, blocks, styles, includes)
+                }
+            }
+        }
+
+        builder
     }
 }
@@ -1,24 +1,37 @@
 mod custom_attributes;
+mod expression;
+mod interpolation;
 mod selector;
 mod tag;
+mod text;
 
-use super::{Attribute, Node, Nodes, Tag};
+use super::{Argument, Attribute, Expression, Node, Nodes, Tag, DEFAULT_SLOT};
 use nom::{
     branch::alt,
-    bytes::complete::{tag, take_till, take_while},
-    combinator::{map, opt},
+    bytes::complete::{tag, take_till, take_while1},
+    character::complete::{anychar, space0, space1},
+    combinator::{cut, eof, map, not, opt, peek, recognize, value},
     multi::{count, many0, many1, separated_list0, separated_list1},
-    sequence::{preceded, terminated},
+    sequence::{pair, preceded, terminated, tuple},
     IResult,
 };
 use std::path::PathBuf;
 
-pub fn parse(input: &str) -> IResult<&str, Nodes> {
+type NodeParser = Box<dyn Fn(&str) -> IResult<&str, Node>>;
+type NodesParser = Box<dyn Fn(&str) -> IResult<&str, Nodes>>;
+type LineParser = Box<dyn Fn(&str) -> IResult<&str, &str>>;
+type BranchParser = Box<dyn Fn(&str) -> IResult<&str, (Expression, Nodes)>>;
+
+pub fn parse(input: &str) -> IResult<&str, Nodes<'_>> {
     let (input, html_attributes) = opt(terminated(
         preceded(tag("!HTML"), opt(custom_attributes::parse)),
         tag("\n"),
     ))(input)?;
-    let (input, children) = alt((parse_fragment_subclass, parse_nodes(0)))(input)?;
+    let (input, children) = match html_attributes {
+        // A layout supplies `!HTML` for the templates that extend it.
+        Some(_) => preceded(not(parse_extends), parse_nodes(0))(input)?,
+        None => alt((parse_fragment_subclass, parse_nodes(0)))(input)?,
+    };
 
     match html_attributes {
         None => Ok((input, children)),
@@ -33,7 +46,7 @@ pub fn parse(input: &str) -> IResult<&str, Nodes> {
     }
 }
 
-fn parse_nodes(depth: usize) -> Box<dyn Fn(&str) -> IResult<&str, Nodes>> {
+fn parse_nodes(depth: usize) -> NodesParser {
     Box::new(move |input| {
         map(
             separated_list0(tag("\n"), preceded(many0(tag("\n")), parse_node(depth))),
@@ -42,72 +55,122 @@ fn parse_nodes(depth: usize) -> Box<dyn Fn(&str) -> IResult<&str, Nodes>> {
     })
 }
 
-fn parse_text_node(input: &str) -> IResult<&str, Node> {
-    map(to_newline, Node::Text)(input)
+fn parse_text_node(input: &str) -> IResult<&str, Node<'_>> {
+    text::parse(input)
 }
 
-fn parse_for_loop(depth: usize) -> Box<dyn Fn(&str) -> IResult<&str, Node>> {
+fn parse_for_loop(depth: usize) -> NodeParser {
     Box::new(move |input| {
-        let (input, local) = preceded(
+        let (input, (key, local)) = preceded(
             tag("- for "),
-            terminated(take_while(|c: char| c.is_alphanumeric()), tag(" in ")),
+            terminated(
+                pair(
+                    opt(terminated(parse_local, pair(tag(","), space0))),
+                    parse_local,
+                ),
+                tag(" in "),
+            ),
         )(input)?;
         let (input, selectors) = terminated(selector::parse, tag("\n"))(input)?;
         let (input, children) = parse_nodes(depth + 1)(input)?;
+        let (input, empty_children) = opt(parse_empty(depth))(input)?;
 
         Ok((
             input,
             Node::ForLoop {
+                key,
                 local,
                 selectors,
                 children,
+                empty_children: empty_children.unwrap_or_default(),
             },
         ))
     })
 }
 
-fn parse_if(depth: usize) -> Box<dyn Fn(&str) -> IResult<&str, Node>> {
+fn parse_empty(depth: usize) -> NodesParser {
+    Box::new(move |input| {
+        let (input, _) = preceded(many1(tag("\n")), count(tag("  "), depth))(input)?;
+        let (input, _) = terminated(alt((tag("- else"), tag("- empty"))), tag("\n"))(input)?;
+        parse_nodes(depth + 1)(input)
+    })
+}
+
+fn parse_local(input: &str) -> IResult<&str, &str> {
+    take_while1(|c: char| c.is_alphanumeric() || c == '_')(input)
+}
+
+fn parse_if(depth: usize) -> NodeParser {
     Box::new(move |input| {
-        let (input, selectors) =
-            preceded(tag("- if "), terminated(selector::parse, tag("\n")))(input)?;
-        let (input, true_children) = parse_nodes(depth + 1)(input)?;
+        let (input, condition) =
+            preceded(tag("- if "), cut(terminated(expression::parse, tag("\n"))))(input)?;
+        let (input, children) = parse_nodes(depth + 1)(input)?;
+        let (input, mut branches) = many0(parse_else_if(depth))(input)?;
+        let (input, false_children) = opt(parse_else(depth))(input)?;
+        branches.insert(0, (condition, children));
 
         Ok((
             input,
             Node::IfElse {
-                selectors,
-                true_children,
-                false_children: Nodes::default(),
+                branches,
+                false_children: false_children.unwrap_or_default(),
             },
         ))
     })
 }
 
-fn parse_if_else(depth: usize) -> Box<dyn Fn(&str) -> IResult<&str, Node>> {
+fn parse_unless(depth: usize) -> NodeParser {
     Box::new(move |input| {
-        let (input, selectors) =
-            preceded(tag("- if "), terminated(selector::parse, tag("\n")))(input)?;
-        let (input, true_children) = parse_nodes(depth + 1)(input)?;
-        let (input, _) = preceded(many1(tag("\n")), count(tag("  "), depth))(input)?;
-        let (input, _) = terminated(tag("- else"), tag("\n"))(input)?;
-        let (input, false_children) = parse_nodes(depth + 1)(input)?;
+        let (input, condition) = preceded(
+            tag("- unless "),
+            cut(terminated(expression::parse, tag("\n"))),
+        )(input)?;
+        let (input, children) = parse_nodes(depth + 1)(input)?;
+        let (input, false_children) = opt(parse_else(depth))(input)?;
 
         Ok((
             input,
             Node::IfElse {
-                selectors,
-                true_children,
-                false_children,
+                branches: vec![(Expression::Not(Box::new(condition)), children)],
+                false_children: false_children.unwrap_or_default(),
             },
         ))
     })
 }
 
-fn parse_markdown_line(depth: usize) -> Box<dyn Fn(&str) -> IResult<&str, &str>> {
+fn parse_else_if(depth: usize) -> BranchParser {
+    Box::new(move |input| {
+        let (input, _) = preceded(many1(tag("\n")), count(tag("  "), depth))(input)?;
+        let (input, condition) = preceded(
+            alt((tag("- else if "), tag("- elsif "))),
+            cut(terminated(expression::parse, tag("\n"))),
+        )(input)?;
+        let (input, children) = parse_nodes(depth + 1)(input)?;
+
+        Ok((input, (condition, children)))
+    })
+}
+
+fn parse_else(depth: usize) -> NodesParser {
+    Box::new(move |input| {
+        let (input, _) = preceded(many1(tag("\n")), count(tag("  "), depth))(input)?;
+        let (input, _) = terminated(tag("- else"), tag("\n"))(input)?;
+        parse_nodes(depth + 1)(input)
+    })
+}
+
+fn parse_let(input: &str) -> IResult<&str, Node<'_>> {
+    let (input, name) = preceded(alt((tag("- let "), tag("- set "))), parse_local)(input)?;
+    let (input, value) = preceded(tuple((space0, tag("="), space0)), interpolation::parse)(input)?;
+
+    Ok((input, Node::Let { name, value }))
+}
+
+fn parse_markdown_line(depth: usize) -> LineParser {
     Box::new(move |input| preceded(count(tag("  "), depth), to_newline)(input))
 }
 
-fn parse_markdown(depth: usize) -> Box<dyn Fn(&str) -> IResult<&str, Node>> {
+fn parse_markdown(depth: usize) -> NodeParser {
     Box::new(move |input| {
         let (input, _) = terminated(tag(":markdown"), many1(tag("\n")))(input)?;
         let (input, markdown) = alt((
@@ -118,17 +181,50 @@ fn parse_markdown(depth: usize) -> Box<dyn Fn(&str) -> IResult<&str, Node>> {
         Ok((input, Node::Markdown(markdown)))
     })
 }
-fn parse_fragment(input: &str) -> IResult<&str, Node> {
-    let (input, path) = map(preceded(tag("- fragment "), to_newline), PathBuf::from)(input)?;
+fn parse_fragment(depth: usize) -> NodeParser {
+    Box::new(move |input| {
+        let (input, path) = map(
+            preceded(
+                tag("- fragment "),
+                recognize(many1(preceded(
+                    not(alt((tag(" with "), tag("\n")))),
+                    anychar,
+                ))),
+            ),
+            PathBuf::from,
+        )(input)?;
+        let (input, arguments) = opt(preceded(
+            tag(" with "),
+            separated_list1(space1, parse_argument),
+        ))(input)?;
+        let (input, children) = parse_nodes(depth + 1)(input)?;
+
+        Ok((
+            input,
+            Node::Fragment {
+                path,
+                arguments: arguments.unwrap_or_default(),
+                slots: children.into_slots(),
+            },
+        ))
+    })
+}
 
-    Ok((input, Node::Fragment { path }))
+fn parse_argument(input: &str) -> IResult<&str, (&str, Argument<'_>)> {
+    pair(
+        terminated(parse_local, tag("=")),
+        alt((
+            map(expression::parse_literal, Argument::Literal),
+            map(interpolation::parse, Argument::Interpolated),
+        )),
+    )(input)
 }
 
 fn parse_extends(input: &str) -> IResult<&str, PathBuf> {
     map(preceded(tag("- extends "), to_newline), PathBuf::from)(input)
 }
 
-fn parse_block_contents(depth: usize) -> Box<dyn Fn(&str) -> IResult<&str, Node>> {
+fn parse_block_contents(depth: usize) -> NodeParser {
     Box::new(move |input| {
         let (input, name) = preceded(tag("- block "), to_newline)(input)?;
         let (input, children) = parse_nodes(depth + 1)(input)?;
@@ -137,10 +233,32 @@ fn parse_block_contents(depth: usize) -> Box<dyn Fn(&str) -> IResult<&str, Node>
     })
 }
 
-fn parse_node_with_text(depth: usize) -> Box<dyn Fn(&str) -> IResult<&str, Node>> {
+fn parse_slot(depth: usize) -> NodeParser {
+    Box::new(move |input| {
+        let (input, name) = alt((
+            preceded(tag("- slot "), to_newline),
+            value(
+                DEFAULT_SLOT,
+                terminated(tag("- yield"), peek(alt((tag("\n"), eof)))),
+            ),
+        ))(input)?;
+        let (input, children) = parse_nodes(depth + 1)(input)?;
+
+        Ok((input, Node::Slot { name, children }))
+    })
+}
+
+fn parse_super(input: &str) -> IResult<&str, Node<'_>> {
+    map(
+        terminated(tag("- super"), peek(alt((tag("\n"), eof)))),
+        |_| Node::Super,
+    )(input)
+}
+
+fn parse_node_with_text(depth: usize) -> NodeParser {
     Box::new(move |input| {
         let (input, tag) = terminated(tag::parse, tag(" "))(input)?;
-        let (input, contents) = map(to_newline, Node::Text)(input)?;
+        let (input, contents) = text::parse(input)?;
         let (input, mut children) = parse_nodes(depth + 1)(input)?;
         children.prepend(contents);
 
@@ -148,15 +266,15 @@ fn parse_node_with_text(depth: usize) -> Box<dyn Fn(&str) -> IResult<&str, Node>
     })
 }
 
-fn parse_interpolated_text(input: &str) -> IResult<&str, Node> {
-    map(selector::parse, Node::InterpolatedText)(input)
+fn parse_interpolated_text(input: &str) -> IResult<&str, Node<'_>> {
+    map(interpolation::parse, Node::InterpolatedText)(input)
 }
 
-fn parse_block_value(input: &str) -> IResult<&str, Node> {
+fn parse_block_value(input: &str) -> IResult<&str, Node<'_>> {
     map(preceded(tag("block "), to_newline), Node::BlockValue)(input)
 }
 
-fn parse_node_with_interpolated_text(depth: usize) -> Box<dyn Fn(&str) -> IResult<&str, Node>> {
+fn parse_node_with_interpolated_text(depth: usize) -> NodeParser {
     Box::new(move |input| {
         let (input, tag) = terminated(tag::parse, tag("= "))(input)?;
         let (input, contents) = alt((parse_block_value, parse_interpolated_text))(input)?;
@@ -167,7 +285,26 @@ fn parse_node_with_interpolated_text(depth: usize) -> Box<dyn Fn(&str) -> IResul
     })
 }
 
-fn parse_node_without_text(depth: usize) -> Box<dyn Fn(&str) -> IResult<&str, Node>> {
+fn parse_raw_interpolated_text(input: &str) -> IResult<&str, Node<'_>> {
+    map(interpolation::parse, Node::RawInterpolatedText)(input)
+}
+
+fn parse_raw_text_node(input: &str) -> IResult<&str, Node<'_>> {
+    preceded(tag("!= "), parse_raw_interpolated_text)(input)
+}
+
+fn parse_node_with_raw_interpolated_text(depth: usize) -> NodeParser {
+    Box::new(move |input| {
+        let (input, tag) = terminated(tag::parse, tag("!= "))(input)?;
+        let (input, contents) = parse_raw_interpolated_text(input)?;
+        let (input, mut children) = parse_nodes(depth + 1)(input)?;
+        children.prepend(contents);
+
+        Ok((input, Node::Element { tag, children }))
+    })
+}
+
+fn parse_node_without_text(depth: usize) -> NodeParser {
     Box::new(move |input| {
         let (input, tag) = tag::parse(input)?;
         let (input, children) = parse_nodes(depth + 1)(input)?;
@@ -176,30 +313,32 @@ fn parse_node_without_text(depth: usize) -> Box<dyn Fn(&str) -> IResult<&str, No
     })
 }
 
-fn parse_node(depth: usize) -> Box<dyn Fn(&str) -> IResult<&str, Node>> {
+fn parse_node(depth: usize) -> NodeParser {
     Box::new(move |input| {
         let (input, _) = count(tag("  "), depth)(input)?;
         alt((
             parse_markdown(depth),
             parse_for_loop(depth),
-            parse_if_else(depth),
             parse_if(depth),
+            parse_unless(depth),
+            parse_let,
+            parse_super,
             parse_block_contents(depth),
-            parse_fragment,
+            parse_slot(depth),
+            parse_fragment(depth),
             parse_node_with_text(depth),
             parse_node_with_interpolated_text(depth),
+            parse_node_with_raw_interpolated_text(depth),
             parse_node_without_text(depth),
+            parse_raw_text_node,
             parse_text_node,
         ))(input)
     })
 }
 
-fn parse_fragment_subclass(input: &str) -> IResult<&str, Nodes> {
-    let (input, layout) = terminated(parse_extends, tag("\n"))(input)?;
-    let (input, blocks) = separated_list1(
-        tag("\n"),
-        preceded(many0(tag("\n")), parse_block_contents(0)),
-    )(input)?;
+fn parse_fragment_subclass(input: &str) -> IResult<&str, Nodes<'_>> {
+    let (input, layout) = parse_extends(input)?;
+    let (input, blocks) = many0(preceded(many1(tag("\n")), parse_block_contents(0)))(input)?;
     let (input, _) = many0(tag("\n"))(input)?;
 
     Ok((input, Nodes::new_fragment_subclass(layout, blocks)))
@@ -211,7 +350,7 @@ pub fn to_newline(input: &str) -> IResult<&str, &str> {
 
 #[cfg(test)]
 mod tests {
-    use super::super::{context::*, Socket};
+    use super::super::{context::*, Socket, SocketError};
 
     #[test]
     fn simple_tag() {
@@ -331,7 +470,7 @@ mod tests {
             Socket::parse("%img(src=\"example.png\" alt=\"What do you think?\")")
                 .unwrap()
                 .to_html(),
-            "<img src=\"example.png\" alt=\"What do you think?\"></img>"
+            "<img src=\"example.png\" alt=\"What do you think?\">"
         );
     }
 
@@ -341,10 +480,121 @@ mod tests {
             Socket::parse("%img(src=example.png alt=\"What do you think?\")")
                 .unwrap()
                 .to_html(),
-            "<img src=\"example.png\" alt=\"What do you think?\"></img>"
+            "<img src=\"example.png\" alt=\"What do you think?\">"
+        );
+    }
+
+    #[test]
+    fn boolean_attributes() {
+        assert_eq!(
+            Socket::parse("%input(type=checkbox checked)\n%script(src=app.js defer)")
+                .unwrap()
+                .to_html(),
+            "<input type=\"checkbox\" checked><script src=\"app.js\" defer></script>"
+        );
+    }
+
+    #[test]
+    fn context_driven_boolean_attributes() {
+        assert_eq!(
+            Socket::parse("- for todo in todos\n  %input(type=checkbox checked={todo.done} title={todo.title})")
+                .unwrap()
+                .with_context(build_context(
+                    "{\"todos\": [{\"done\": true, \"title\": \"Write\"}, {\"done\": false, \"title\": \"Ship\"}, {\"done\": null, \"title\": \"Rest\"}]}"
+                ))
+                .map(|v| v.to_html())
+                .unwrap(),
+            "<input type=\"checkbox\" checked title=\"Write\"><input type=\"checkbox\" title=\"Ship\"><input type=\"checkbox\" title=\"Rest\">"
+        );
+    }
+
+    #[test]
+    fn context_driven_enumerated_attributes() {
+        let mut parsed = Socket::parse(
+            "%button(aria-expanded={open} draggable={open} disabled={open})\n%input(required={missing})",
+        )
+        .unwrap();
+        let socket = parsed
+            .with_context(build_context("{\"open\": false}"))
+            .unwrap();
+        let rendered = socket.render();
+
+        assert_eq!(
+            rendered.html,
+            "<button aria-expanded=\"false\" draggable=\"false\"></button><input>"
+        );
+        assert_eq!(
+            rendered
+                .errors
+                .iter()
+                .map(|e| e.to_string())
+                .collect::<Vec<_>>(),
+            vec!["no value at `missing`"]
+        );
+    }
+
+    #[test]
+    fn dynamic_classes() {
+        assert_eq!(
+            Socket::parse("- for item in items\n  %li.nav-item(class={item.classes})= item.name\n%p.note(class=\"note-{kind} extra\")")
+                .unwrap()
+                .with_context(build_context(
+                    "{\"kind\": \"info\", \"items\": [{\"name\": \"Home\", \"classes\": {\"active\": true, \"disabled\": false}}, {\"name\": \"About\", \"classes\": [\"wide\", \"nav-item\"]}, {\"name\": \"Blog\", \"classes\": \"faded small\"}, {\"name\": \"Help\"}]}"
+                ))
+                .map(|v| v.to_html())
+                .unwrap(),
+            "<li class=\"nav-item active\">Home</li><li class=\"nav-item wide\">About</li><li class=\"nav-item faded small\">Blog</li><li class=\"nav-item\">Help</li><p class=\"note note-info extra\"></p>"
+        );
+    }
+
+    #[test]
+    fn void_elements() {
+        assert_eq!(
+            Socket::parse(
+                "%head\n  %meta(charset=utf-8)\n  %link(rel=stylesheet href=app.css)\n%br"
+            )
+            .unwrap()
+            .to_html(),
+            "<head><meta charset=\"utf-8\"><link rel=\"stylesheet\" href=\"app.css\"></head><br>"
+        );
+    }
+
+    #[test]
+    fn void_elements_with_children() {
+        let rendered = Socket::parse("%img(src=example.png) Caption")
+            .unwrap()
+            .render();
+        assert_eq!(rendered.html, "<img src=\"example.png\">");
+        assert_eq!(
+            rendered.errors[0].to_string(),
+            "void element `img` cannot have children"
         );
     }
 
+    #[test]
+    fn void_elements_with_trailing_whitespace() {
+        let rendered = Socket::parse("%p\n  %br \n  %hr   \n%img(src=example.png) ")
+            .unwrap()
+            .render();
+        assert_eq!(rendered.html, "<p><br><hr></p><img src=\"example.png\">");
+        assert!(rendered.errors.is_empty());
+    }
+
+    #[test]
+    fn incomplete_parse() {
+        match Socket::parse("%ul\n  %li One\n  %li(class=two Two\n  %li Three") {
+            Err(SocketError::IncompleteParse(diagnostic)) => {
+                assert_eq!(diagnostic.line, 3);
+                assert_eq!(diagnostic.column, 6);
+                assert_eq!(
+                    diagnostic.message,
+                    "unterminated attribute list, expected `)`"
+                );
+            }
+            _ => panic!("expected an incomplete parse"),
+        }
+    }
+
     #[test]
     fn html_doctype_with_attributes() {
         assert_eq!(
@@ -389,6 +639,34 @@ mod tests {
         );
     }
 
+    #[test]
+    fn collection_interpolation() {
+        let mut parsed =
+            Socket::parse("%p= tags\n%p= author\n%pre= author | json\n%p= nested").unwrap();
+        let socket = parsed
+            .with_context(build_context(
+                "{\"tags\": [\"rust\", 1, true], \"author\": {\"name\": \"Jane\"}, \"nested\": [[1]]}",
+            ))
+            .unwrap();
+        let rendered = socket.render();
+
+        assert_eq!(
+            rendered.html,
+            "<p>rust, 1, true</p><p></p><pre>{\"name\":\"Jane\"}</pre><p></p>"
+        );
+        assert_eq!(
+            rendered
+                .errors
+                .iter()
+                .map(|e| e.to_string())
+                .collect::<Vec<_>>(),
+            vec![
+                "value at `author` cannot be rendered as text, use the `json` filter",
+                "value at `nested` cannot be rendered as text, use the `json` filter",
+            ]
+        );
+    }
+
     #[test]
     fn attribute_interpolation() {
         assert_eq!(
@@ -401,6 +679,159 @@ mod tests {
         );
     }
 
+    #[test]
+    fn attribute_interpolation_errors() {
+        let mut parsed =
+            Socket::parse("%a(href={missing})\n%div(data-author={author} title=\"by {author}\")")
+                .unwrap();
+        let socket = parsed
+            .with_context(build_context("{\"author\": {\"name\": \"Jane\"}}"))
+            .unwrap();
+        let rendered = socket.render();
+
+        assert_eq!(
+            rendered.html,
+            "<a href=\"\"></a><div data-author=\"\" title=\"by \"></div>"
+        );
+        assert_eq!(
+            rendered
+                .errors
+                .iter()
+                .map(|e| e.to_string())
+                .collect::<Vec<_>>(),
+            vec![
+                "no value at `missing`",
+                "value at `author` cannot be rendered as text, use the `json` filter",
+                "value at `author` cannot be rendered as text, use the `json` filter",
+            ]
+        );
+        assert_eq!(
+            socket.diagnostic(&rendered.errors[0]).unwrap().to_string(),
+            "warning: no value at `missing`\n --> <input>:1:10\n  |\n1 | %a(href={missing})\n  |          ^"
+        );
+    }
+
+    #[test]
+    fn sockets_can_be_sent_between_threads() {
+        fn assert_send<T: Send>() {}
+
+        assert_send::<Socket>();
+        assert_send::<Context>();
+    }
+
+    #[test]
+    fn interpolation_is_escaped() {
+        assert_eq!(
+            Socket::parse("%a(title={link.title})= link.text")
+                .unwrap()
+                .with_context(build_context(
+                    "{\"link\": {\"title\": \"\\\"quoted\\\" & <b>\", \"text\": \"<i>Tom & Jerry</i>\"}}"
+                ))
+                .map(|v| v.to_html())
+                .unwrap(),
+            "<a title=\"&quot;quoted&quot; &amp; &lt;b&gt;\">&lt;i&gt;Tom &amp; Jerry&lt;/i&gt;</a>"
+        );
+    }
+
+    #[test]
+    fn raw_interpolation_is_not_escaped() {
+        assert_eq!(
+            Socket::parse("%div!= snippet\n%p\n  != snippet")
+                .unwrap()
+                .with_context(build_context("{\"snippet\": \"<em>Trusted & true</em>\"}"))
+                .map(|v| v.to_html())
+                .unwrap(),
+            "<div><em>Trusted & true</em></div><p><em>Trusted & true</em></p>"
+        );
+    }
+
+    #[test]
+    fn render_collects_errors() {
+        let input = "%ul\n  - for person in people\n    %li= person.email";
+        let mut parsed = Socket::parse(input).unwrap();
+        let socket = parsed
+            .with_context(build_context("{\"people\": [{\"name\": \"Jane\"}]}"))
+            .unwrap();
+        let rendered = socket.render();
+
+        assert_eq!(rendered.html, "<ul><li></li></ul>");
+        assert_eq!(rendered.errors.len(), 1);
+        assert_eq!(
+            socket.diagnostic(&rendered.errors[0]).unwrap().to_string(),
+            "warning: no value at `person.email`\n --> <input>:3:10\n  |\n3 |     %li= person.email\n  |          ^"
+        );
+    }
+
+    #[test]
+    fn strict_rendering() {
+        let mut parsed = Socket::parse("%h1= title\n%p= missing").unwrap();
+        let socket = parsed
+            .with_context(build_context("{\"title\": \"Hello\"}"))
+            .unwrap();
+
+        assert_eq!(socket.try_render().unwrap().html, "<h1>Hello</h1><p></p>");
+        assert_eq!(
+            socket
+                .with_strict(true)
+                .try_render()
+                .err()
+                .map(|errors| errors.len()),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn inline_interpolation() {
+        assert_eq!(
+            Socket::parse("%p Hello, {user.name}! You have {count} messages.\n%div\n  Signed in as {user.name}")
+                .unwrap()
+                .with_context(build_context(
+                    "{\"user\": {\"name\": \"<Jane>\"}, \"count\": 3}"
+                ))
+                .map(|v| v.to_html())
+                .unwrap(),
+            "<p>Hello, &lt;Jane&gt;! You have 3 messages.</p><div>Signed in as &lt;Jane&gt;</div>"
+        );
+    }
+
+    #[test]
+    fn filters() {
+        assert_eq!(
+            Socket::parse("%h1= title | upcase\n%p Tags: {tags | join(\", \")} at {price | number(2)}\n%a(href={url | default(\"#\")})= missing | default(\"n/a\")")
+                .unwrap()
+                .with_context(build_context(
+                    "{\"title\": \"Hello\", \"tags\": [\"rust\", \"html\"], \"price\": 4.5}"
+                ))
+                .map(|v| v.to_html())
+                .unwrap(),
+            "<h1>HELLO</h1><p>Tags: rust, html at 4.50</p><a href=\"#\">n/a</a>"
+        );
+    }
+
+    #[test]
+    fn custom_filters() {
+        let mut parsed = Socket::parse("%p= name | reverse\n%p= name | shout").unwrap();
+        let socket = parsed
+            .with_filter("reverse", |value, _| {
+                Ok(value
+                    .as_str()
+                    .unwrap_or_default()
+                    .chars()
+                    .rev()
+                    .collect::<String>()
+                    .into())
+            })
+            .with_context(build_context("{\"name\": \"Jane\"}"))
+            .unwrap();
+        let rendered = socket.render();
+
+        assert_eq!(rendered.html, "<p>enaJ</p><p></p>");
+        assert_eq!(
+            socket.diagnostic(&rendered.errors[0]).unwrap().to_string(),
+            "warning: unknown filter `shout`\n --> <input>:2:12\n  |\n2 | %p= name | shout\n  |            ^"
+        );
+    }
+
     #[test]
     fn for_loops() {
         assert_eq!(
@@ -450,43 +881,259 @@ mod tests {
     }
 
     #[test]
-    fn nested_if_statement() {
-        assert_eq!(
-            Socket::parse("- if flag\n  - if otherflag\n    %p= works\n- else\n  %p= works")
+    fn if_expressions() {
+        let context = "{\"user\": {\"admin\": false, \"name\": \"Jane\"}, \"items\": [1, 2], \"status\": \"draft\", \"a\": true, \"b\": false, \"c\": true}";
+        let render = |input: &str| {
+            Socket::parse(input)
                 .unwrap()
-                .with_context(build_context(
-                    "{\"works\": \"sure does\", \"otherflag\": false, \"flag\": true}"
-                ))
+                .with_context(build_context(context))
                 .map(|v| v.to_html())
-                .unwrap(),
-            ""
+                .unwrap()
+        };
+
+        assert_eq!(render("- if not user.admin\n  %p yes"), "<p>yes</p>");
+        assert_eq!(render("- if items.length > 0\n  %p yes"), "<p>yes</p>");
+        assert_eq!(render("- if items.length >= 3\n  %p yes"), "");
+        assert_eq!(render("- if status == \"draft\"\n  %p yes"), "<p>yes</p>");
+        assert_eq!(render("- if status != \"draft\"\n  %p yes"), "");
+        assert_eq!(render("- if a and (b or c)\n  %p yes"), "<p>yes</p>");
+        assert_eq!(
+            render("- if a and b or not c\n  %p yes\n- else\n  %p no"),
+            "<p>no</p>"
+        );
+        assert_eq!(
+            render("- if user.name == \"Jane\" and items[1] == 2\n  %p yes"),
+            "<p>yes</p>"
         );
+    }
+
+    #[test]
+    fn if_truthiness() {
+        let context = "{\"avatar\": \"me.png\", \"blank\": \"\", \"none\": null, \"zero\": 0, \"count\": 2, \"empty\": [], \"list\": [1], \"nothing\": {}, \"thing\": {\"a\": 1}}";
+        let render = |input: &str| {
+            Socket::parse(input)
+                .unwrap()
+                .with_context(build_context(context))
+                .map(|v| {
+                    let rendered = v.render();
+                    (rendered.html, rendered.errors.len())
+                })
+                .unwrap()
+        };
+
+        for truthy in &["avatar", "count", "list", "thing", "not blank"] {
+            let rendered = render(&format!("- if {}\n  %p yes\n- else\n  %p no", truthy));
+            assert_eq!(rendered, ("<p>yes</p>".into(), 0), "{} is truthy", truthy);
+        }
+
+        for falsy in &["blank", "none", "zero", "empty", "nothing", "not avatar"] {
+            let rendered = render(&format!("- if {}\n  %p yes\n- else\n  %p no", falsy));
+            assert_eq!(rendered, ("<p>no</p>".into(), 0), "{} is falsy", falsy);
+        }
 
+        for missing in &["missing", "missing.length > 0", "not missing and avatar"] {
+            let rendered = render(&format!("- if {}\n  %p yes\n- else\n  %p no", missing));
+            let expected = if missing.starts_with("not") {
+                "<p>yes</p>"
+            } else {
+                "<p>no</p>"
+            };
+            assert_eq!(rendered, (expected.into(), 1), "{} warns", missing);
+        }
+    }
+
+    #[test]
+    fn else_if_chains() {
+        let render = |status: &str| {
+            Socket::parse("- if status == \"draft\"\n  %p Draft\n- else if status == \"review\"\n  %p In review\n\n- elsif status == \"published\"\n  %p Published\n- else\n  %p Unknown")
+                .unwrap()
+                .with_context(build_context(&format!("{{\"status\": \"{}\"}}", status)))
+                .map(|v| v.to_html())
+                .unwrap()
+        };
+
+        assert_eq!(render("draft"), "<p>Draft</p>");
+        assert_eq!(render("review"), "<p>In review</p>");
+        assert_eq!(render("published"), "<p>Published</p>");
+        assert_eq!(render("archived"), "<p>Unknown</p>");
+    }
+
+    #[test]
+    fn else_if_without_else() {
         assert_eq!(
             Socket::parse(
-                r#"
-- if flag
-  - if otherflag
-    %p= works
-
-  - else
-    %p do not get here"#
+                "%div\n  - if first\n    %p First\n  - else if second\n    %p Second\n  %p After"
             )
             .unwrap()
-            .with_context(build_context(
-                "{\"works\": \"sure does\", \"otherflag\": true, \"flag\": true}"
-            ))
+            .with_context(build_context("{\"first\": false, \"second\": true}"))
             .map(|v| v.to_html())
             .unwrap(),
-            "<p>sure does</p>"
+            "<div><p>Second</p><p>After</p></div>"
         );
     }
 
     #[test]
-    fn for_loops_with_object() {
+    fn unless_statement() {
+        let render = |context: &str| {
+            Socket::parse(
+                "- unless user.verified\n  %p Please verify your email\n- else\n  %p Thanks",
+            )
+            .unwrap()
+            .with_context(build_context(context))
+            .map(|v| v.to_html())
+            .unwrap()
+        };
+
         assert_eq!(
-            Socket::parse("%ul\n  - for value in values\n    %li= value.name")
-                .unwrap()
+            render("{\"user\": {\"verified\": false}}"),
+            "<p>Please verify your email</p>"
+        );
+        assert_eq!(render("{\"user\": {}}"), "<p>Please verify your email</p>");
+        assert_eq!(render("{\"user\": {\"verified\": true}}"), "<p>Thanks</p>");
+        assert_eq!(
+            Socket::parse("- unless hidden\n  %p Shown")
+                .unwrap()
+                .with_context(build_context("{\"hidden\": true}"))
+                .map(|v| v.to_html())
+                .unwrap(),
+            ""
+        );
+    }
+
+    #[test]
+    fn malformed_conditions() {
+        for source in &[
+            "- if n \n  %p zero",
+            "- if not(x)\n  %p x",
+            "- if a and\n  %p a",
+            "- unless a ==\n  %p a",
+            "- if a\n  %p a\n- else if b or\n  %p b",
+            "- if a\n  %p a\n- elsif (b\n  %p b",
+        ] {
+            match Socket::parse(source) {
+                Err(SocketError::ParseError(_)) => (),
+                _ => panic!("expected a parse error for {:?}", source),
+            }
+        }
+    }
+
+    #[test]
+    fn nested_if_statement() {
+        assert_eq!(
+            Socket::parse("- if flag\n  - if otherflag\n    %p= works\n- else\n  %p= works")
+                .unwrap()
+                .with_context(build_context(
+                    "{\"works\": \"sure does\", \"otherflag\": false, \"flag\": true}"
+                ))
+                .map(|v| v.to_html())
+                .unwrap(),
+            ""
+        );
+
+        assert_eq!(
+            Socket::parse(
+                r#"
+- if flag
+  - if otherflag
+    %p= works
+
+  - else
+    %p do not get here"#
+            )
+            .unwrap()
+            .with_context(build_context(
+                "{\"works\": \"sure does\", \"otherflag\": true, \"flag\": true}"
+            ))
+            .map(|v| v.to_html())
+            .unwrap(),
+            "<p>sure does</p>"
+        );
+    }
+
+    #[test]
+    fn for_loop_metadata() {
+        assert_eq!(
+            Socket::parse("%ul\n  - for value in values\n    %li(class=\"row-{loop.index}\" data-first={loop.first})\n      {value} ({loop.index0}/{loop.length})\n      - unless loop.last\n        ,")
+                .unwrap()
+                .with_context(build_context(
+                    "{\"values\": [\"first\", \"second\", \"third\"]}"
+                ))
+                .map(|v| v.to_html())
+                .unwrap(),
+            "<ul><li class=\"row-1\" data-first=\"true\">first (0/3),</li><li class=\"row-2\" data-first=\"false\">second (1/3),</li><li class=\"row-3\" data-first=\"false\">third (2/3)</li></ul>"
+        );
+    }
+
+    #[test]
+    fn for_loops_over_object_entries() {
+        assert_eq!(
+            Socket::parse("%dl\n  - for key, value in settings\n    %dt= key\n    %dd= value")
+                .unwrap()
+                .with_context(build_context(
+                    "{\"settings\": {\"theme\": \"dark\", \"language\": \"en\", \"beta\": true}}"
+                ))
+                .map(|v| v.to_html())
+                .unwrap(),
+            "<dl><dt>beta</dt><dd>true</dd><dt>language</dt><dd>en</dd><dt>theme</dt><dd>dark</dd></dl>"
+        );
+
+        assert_eq!(
+            Socket::parse("- for i,value in values\n  %p {i}: {value}")
+                .unwrap()
+                .with_context(build_context("{\"values\": [\"a\", \"b\"]}"))
+                .map(|v| v.to_html())
+                .unwrap(),
+            "<p>0: a</p><p>1: b</p>"
+        );
+    }
+
+    #[test]
+    fn for_loops_with_empty_clause() {
+        let render = |context: &str| {
+            Socket::parse("%ul\n  - for item in items\n    %li= item\n  - empty\n    %li.empty No items\n%ol\n  - for item in items\n    %li= item\n  - else\n    %li None")
+                .unwrap()
+                .with_context(build_context(context))
+                .map(|v| v.to_html())
+                .unwrap()
+        };
+
+        assert_eq!(
+            render("{\"items\": [\"one\"]}"),
+            "<ul><li>one</li></ul><ol><li>one</li></ol>"
+        );
+        assert_eq!(
+            render("{\"items\": []}"),
+            "<ul><li class=\"empty\">No items</li></ul><ol><li>None</li></ol>"
+        );
+        assert_eq!(
+            render("{}"),
+            "<ul><li class=\"empty\">No items</li></ul><ol><li>None</li></ol>"
+        );
+    }
+
+    #[test]
+    fn let_bindings() {
+        let mut parsed = Socket::parse("- let author = post.author\n%h1= author.name\n%ul\n  - for tag in post.tags\n    - set label = tag | upcase\n    %li= label\n%p= label").unwrap();
+        let socket = parsed
+            .with_context(build_context(
+                "{\"post\": {\"author\": {\"name\": \"Jane\"}, \"tags\": [\"a\", \"b\"]}}",
+            ))
+            .unwrap();
+        let rendered = socket.render();
+
+        assert_eq!(
+            rendered.html,
+            "<h1>Jane</h1><ul><li>A</li><li>B</li></ul><p></p>"
+        );
+        assert_eq!(rendered.errors.len(), 1);
+        assert_eq!(rendered.errors[0].to_string(), "no value at `label`");
+    }
+
+    #[test]
+    fn for_loops_with_object() {
+        assert_eq!(
+            Socket::parse("%ul\n  - for value in values\n    %li= value.name")
+                .unwrap()
                 .with_context(build_context(
                     "{\"values\": [{\"name\": \"Jane\"}, {\"name\": \"John\"}]}"
                 ))
@@ -519,6 +1166,138 @@ mod tests {
         )
     }
 
+    #[test]
+    fn fragments_with_slots() {
+        use std::collections::HashMap;
+        use std::path::PathBuf;
+
+        let mut fragments: HashMap<PathBuf, String> = HashMap::new();
+        fragments.insert(
+            PathBuf::from("modal.skt"),
+            "%div.modal\n  %header\n    - slot title\n      %h2 Notice\n  - yield\n  %footer\n    - slot actions"
+                .into(),
+        );
+
+        assert_eq!(
+            Socket::parse("- fragment modal.skt\n  - block title\n    %h2= heading\n  %p Are you sure?\n  %p This cannot be undone.\n- fragment modal.skt\n  %p Saved")
+                .unwrap()
+                .with_fragments(&fragments)
+                .with_context(build_context("{\"heading\": \"Delete\"}"))
+                .map(|v| v.to_html())
+                .unwrap(),
+            "<div class=\"modal\"><header><h2>Delete</h2></header><p>Are you sure?</p><p>This cannot be undone.</p><footer></footer></div><div class=\"modal\"><header><h2>Notice</h2></header><p>Saved</p><footer></footer></div>"
+        );
+    }
+
+    #[test]
+    fn composed_components() {
+        use std::collections::HashMap;
+        use std::path::PathBuf;
+
+        let mut fragments: HashMap<PathBuf, String> = HashMap::new();
+        fragments.insert(
+            PathBuf::from("box.skt"),
+            "%div.box\n  - slot title\n  - yield".into(),
+        );
+        fragments.insert(
+            PathBuf::from("wrapper.skt"),
+            "- fragment box.skt\n  - yield\n  - block title\n    - slot title\n      %h2 Untitled"
+                .into(),
+        );
+        fragments.insert(
+            PathBuf::from("modal.skt"),
+            "%div.modal\n  - yield\n  - fragment button.skt".into(),
+        );
+        fragments.insert(
+            PathBuf::from("button.skt"),
+            "%button\n  - yield\n    OK".into(),
+        );
+
+        let render = |input: &str| {
+            let rendered = Socket::parse(input)
+                .unwrap()
+                .with_fragments(&fragments)
+                .render();
+            assert!(rendered.errors.is_empty());
+
+            rendered.html
+        };
+
+        assert_eq!(
+            render("- fragment wrapper.skt\n  %p passed"),
+            "<div class=\"box\"><h2>Untitled</h2><p>passed</p></div>"
+        );
+        assert_eq!(
+            render("- fragment wrapper.skt\n  - block title\n    %h2 Custom\n  %p passed"),
+            "<div class=\"box\"><h2>Custom</h2><p>passed</p></div>"
+        );
+        assert_eq!(
+            render("- fragment modal.skt\n  %p body"),
+            "<div class=\"modal\"><p>body</p><button>OK</button></div>"
+        );
+    }
+
+    #[test]
+    fn slot_content_uses_call_site_context() {
+        use std::collections::HashMap;
+        use std::path::PathBuf;
+
+        let mut fragments: HashMap<PathBuf, String> = HashMap::new();
+        fragments.insert(
+            PathBuf::from("card.skt"),
+            "%div.card\n  %h2= person.name\n  - yield".into(),
+        );
+
+        let mut parsed = Socket::parse(
+            "- let site = meta.site\n- for item in items\n  - fragment card.skt with person=item\n    %p= item.role\n    %p= site",
+        )
+        .unwrap();
+        let socket = parsed
+            .with_fragments(&fragments)
+            .with_context(build_context(
+                "{\"meta\": {\"site\": \"Example\"}, \"items\": [{\"name\": \"Jane\", \"role\": \"Editor\"}]}",
+            ))
+            .unwrap();
+        let rendered = socket.render();
+
+        assert_eq!(
+            rendered.html,
+            "<div class=\"card\"><h2>Jane</h2><p>Editor</p><p>Example</p></div>"
+        );
+        assert!(rendered.errors.is_empty());
+    }
+
+    #[test]
+    fn fragments_with_arguments() {
+        use std::collections::HashMap;
+        use std::path::PathBuf;
+
+        let mut fragments: HashMap<PathBuf, String> = HashMap::new();
+        fragments.insert(
+            PathBuf::from("card.skt"),
+            "%div(data-count={count})\n  %h2= title\n  %p= person.name | upcase\n  %p= site".into(),
+        );
+
+        let mut parsed = Socket::parse(
+            "- for item in items\n  - fragment card.skt with person=item title=\"Hello\" count=2",
+        )
+        .unwrap();
+        let socket = parsed
+            .with_fragments(&fragments)
+            .with_context(build_context(
+                "{\"site\": \"Example\", \"items\": [{\"name\": \"Jane\"}]}",
+            ))
+            .unwrap();
+        let rendered = socket.render();
+
+        assert_eq!(
+            rendered.html,
+            "<div data-count=\"2\"><h2>Hello</h2><p>JANE</p><p></p></div>"
+        );
+        assert_eq!(rendered.errors.len(), 1);
+        assert_eq!(rendered.errors[0].to_string(), "no value at `site`");
+    }
+
     #[test]
     fn extends_and_blocks() {
         use std::collections::HashMap;
@@ -544,6 +1323,240 @@ mod tests {
         )
     }
 
+    #[test]
+    fn multi_level_extends() {
+        use std::collections::HashMap;
+        use std::path::PathBuf;
+
+        let mut fragments: HashMap<PathBuf, String> = HashMap::new();
+        fragments.insert(
+            PathBuf::from("base.skt"),
+            "%body\n  - block header\n    %h1 Base\n  - block content\n    %p Base content".into(),
+        );
+        fragments.insert(
+            PathBuf::from("section.skt"),
+            "- extends base.skt\n- block header\n  %h1 Section\n- block content\n  .section\n    - super\n    - block sidebar\n      %aside Default sidebar".into(),
+        );
+        fragments.insert(
+            PathBuf::from("page.skt"),
+            "- extends section.skt\n- block content\n  - super\n  %p Page\n- block sidebar\n  %aside Page sidebar".into(),
+        );
+
+        assert_eq!(
+            Socket::parse("- fragment page.skt")
+                .unwrap()
+                .with_fragments(&fragments)
+                .to_html(),
+            "<body><h1>Section</h1><div class=\"section\"><p>Base content</p><aside>Page sidebar</aside></div><p>Page</p></body>",
+        );
+
+        let rendered = Socket::parse("%p\n  - super").unwrap().render();
+        assert_eq!(
+            rendered.errors[0].to_string(),
+            "`- super` used outside a block override"
+        );
+    }
+
+    #[test]
+    fn components_extending_their_own_layouts() {
+        use std::collections::HashMap;
+        use std::path::PathBuf;
+
+        let mut fragments: HashMap<PathBuf, String> = HashMap::new();
+        fragments.insert(
+            PathBuf::from("layouts/base.skt"),
+            "%main\n  - block content".into(),
+        );
+        fragments.insert(
+            PathBuf::from("layouts/card.skt"),
+            "%div.card\n  - block content".into(),
+        );
+        fragments.insert(
+            PathBuf::from("card.skt"),
+            "- extends layouts/card.skt\n- block content\n  %p Card body".into(),
+        );
+
+        let rendered = Socket::parse(
+            "- extends layouts/base.skt\n- block content\n  %h1 Page\n  - fragment card.skt",
+        )
+        .unwrap()
+        .with_fragments(&fragments)
+        .render();
+
+        assert_eq!(
+            rendered.html,
+            "<main><h1>Page</h1><div class=\"card\"><p>Card body</p></div></main>"
+        );
+        assert!(rendered.errors.is_empty());
+    }
+
+    #[test]
+    fn nested_blocks_with_the_same_name() {
+        use std::collections::HashMap;
+        use std::path::PathBuf;
+
+        let mut fragments: HashMap<PathBuf, String> = HashMap::new();
+        fragments.insert(
+            PathBuf::from("base.skt"),
+            "%main\n  - block content\n  - block sidebar".into(),
+        );
+
+        let render = |input: &str| {
+            let rendered = Socket::parse(input)
+                .unwrap()
+                .with_fragments(&fragments)
+                .render();
+            let errors: Vec<String> = rendered.errors.iter().map(|e| e.to_string()).collect();
+
+            (rendered.html, errors)
+        };
+
+        assert_eq!(
+            render("- extends base.skt\n- block content\n  %section\n    - block content\n      %p Nested"),
+            ("<main><section><p>Nested</p></section></main>".to_string(), vec![])
+        );
+        assert_eq!(
+            render("- extends base.skt\n- block content\n  - block sidebar\n- block sidebar\n  - block content"),
+            (
+                "<main></main>".to_string(),
+                vec![
+                    "block cycle: content -> sidebar -> content".to_string(),
+                    "block cycle: sidebar -> content -> sidebar".to_string()
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn fragment_parse_errors() {
+        use std::collections::HashMap;
+        use std::path::PathBuf;
+
+        let mut fragments: HashMap<PathBuf, String> = HashMap::new();
+        fragments.insert(PathBuf::from("broken.skt"), "%div\n  %p(class=foo".into());
+
+        let mut parsed = Socket::parse("%main\n  - fragment broken.skt").unwrap();
+        let socket = parsed.with_fragments(&fragments);
+        let rendered = socket.render();
+
+        assert_eq!(rendered.html, "<main></main>");
+        assert_eq!(
+            rendered.errors[0].to_string(),
+            "invalid fragment `broken.skt`: unterminated attribute list, expected `)`"
+        );
+        assert_eq!(
+            socket.diagnostic(&rendered.errors[0]).unwrap().to_string(),
+            "error: unterminated attribute list, expected `)`\n --> broken.skt:2:5\n  |\n2 |   %p(class=foo\n  |     ^"
+        );
+    }
+
+    #[test]
+    fn fragments_nested_in_themselves() {
+        use std::collections::HashMap;
+        use std::path::PathBuf;
+
+        let mut fragments: HashMap<PathBuf, String> = HashMap::new();
+        fragments.insert(PathBuf::from("box.skt"), "%div.box\n  - yield".into());
+
+        let rendered = Socket::parse("- fragment box.skt\n  - fragment box.skt\n    %p inner")
+            .unwrap()
+            .with_fragments(&fragments)
+            .render();
+
+        assert_eq!(
+            rendered.html,
+            "<div class=\"box\"><div class=\"box\"><p>inner</p></div></div>"
+        );
+        assert!(rendered.errors.is_empty());
+    }
+
+    #[test]
+    fn fragment_cycles() {
+        use std::collections::HashMap;
+        use std::path::PathBuf;
+
+        let mut fragments: HashMap<PathBuf, String> = HashMap::new();
+        fragments.insert(
+            PathBuf::from("self.skt"),
+            "%p\n  - fragment self.skt".into(),
+        );
+        fragments.insert(PathBuf::from("a.skt"), "%a\n  - fragment b.skt".into());
+        fragments.insert(PathBuf::from("b.skt"), "%b\n  - fragment a.skt".into());
+        fragments.insert(
+            PathBuf::from("layout.skt"),
+            "- extends layout.skt\n- block content\n  %p Hi".into(),
+        );
+
+        let render = |input: &str| {
+            let rendered = Socket::parse(input)
+                .unwrap()
+                .with_fragments(&fragments)
+                .render();
+            let errors: Vec<String> = rendered.errors.iter().map(|e| e.to_string()).collect();
+
+            (rendered.html, errors)
+        };
+
+        assert_eq!(
+            render("- fragment self.skt"),
+            (
+                "<p></p>".to_string(),
+                vec!["fragment cycle: self.skt -> self.skt".to_string()]
+            )
+        );
+        assert_eq!(
+            render("- fragment a.skt"),
+            (
+                "<a><b></b></a>".to_string(),
+                vec!["fragment cycle: a.skt -> b.skt -> a.skt".to_string()]
+            )
+        );
+        assert_eq!(
+            render("- fragment layout.skt"),
+            (
+                String::new(),
+                vec!["fragment cycle: layout.skt -> layout.skt".to_string()]
+            )
+        );
+    }
+
+    #[test]
+    fn top_level_extends() {
+        use std::collections::HashMap;
+        use std::path::PathBuf;
+
+        let mut fragments: HashMap<PathBuf, String> = HashMap::new();
+        fragments.insert(
+            PathBuf::from("layouts/base.skt"),
+            "!HTML\n%body\n  - block content\n    %p Default".into(),
+        );
+
+        assert_eq!(
+            Socket::parse("- extends layouts/base.skt\n")
+                .unwrap()
+                .with_fragments(&fragments)
+                .to_html(),
+            "<!DOCTYPE html><html><body><p>Default</p></body></html>",
+        );
+
+        match Socket::parse("- extends layouts/base.skt\n\n%p Stray") {
+            Err(SocketError::IncompleteParse(diagnostic)) => assert_eq!(diagnostic.line, 3),
+            _ => panic!("expected an incomplete parse"),
+        }
+
+        match Socket::parse("!HTML\n- extends layouts/base.skt\n- block content\n  %p Page") {
+            Err(SocketError::ParseError(diagnostic)) => {
+                assert_eq!(diagnostic.line, 2);
+                assert_eq!(diagnostic.column, 1);
+                assert_eq!(
+                    diagnostic.message,
+                    "`- extends` must be the first line, the layout provides `!HTML`"
+                );
+            }
+            _ => panic!("expected a parse error"),
+        }
+    }
+
     #[test]
     fn markdown_support() {
         assert_eq!(
@@ -1,15 +1,15 @@
 use super::super::{Attribute, AttributeValueComponent};
-use super::selector;
+use super::interpolation;
 use nom::{
     branch::alt,
-    bytes::complete::{tag, take_till1, take_while},
+    bytes::complete::{tag, take_till1, take_while1},
     combinator::map,
     multi::{many0, separated_list1},
     sequence::{pair, preceded, terminated},
     IResult,
 };
 
-fn wrapped_string(input: &str) -> IResult<&str, Vec<AttributeValueComponent>> {
+fn wrapped_string(input: &str) -> IResult<&str, Vec<AttributeValueComponent<'_>>> {
     let (input, base) = preceded(tag("\""), alt((raw_quoted, interpolated)))(input)?;
     let (input, mut rest) = terminated(many0(alt((raw_quoted, interpolated))), tag("\""))(input)?;
 
@@ -18,29 +18,28 @@ fn wrapped_string(input: &str) -> IResult<&str, Vec<AttributeValueComponent>> {
     Ok((input, rest))
 }
 
-fn raw_quoted(input: &str) -> IResult<&str, AttributeValueComponent> {
+fn raw_quoted(input: &str) -> IResult<&str, AttributeValueComponent<'_>> {
     map(
         take_till1(|c: char| c == '\"' || c == '{'),
         AttributeValueComponent::RawValue,
     )(input)
 }
 
-fn raw_unwrapped(input: &str) -> IResult<&str, AttributeValueComponent> {
+fn raw_unwrapped(input: &str) -> IResult<&str, AttributeValueComponent<'_>> {
     map(
         take_till1(|c: char| c.is_whitespace() || c == '=' || c == ')' || c == '{'),
         AttributeValueComponent::RawValue,
     )(input)
 }
 
-fn interpolated(input: &str) -> IResult<&str, AttributeValueComponent> {
-    let parse_interpolated_selectors = preceded(tag("{"), terminated(selector::parse, tag("}")));
+fn interpolated(input: &str) -> IResult<&str, AttributeValueComponent<'_>> {
     map(
-        parse_interpolated_selectors,
+        interpolation::braced,
         AttributeValueComponent::InterpolatedValue,
     )(input)
 }
 
-fn unwrapped_string(input: &str) -> IResult<&str, Vec<AttributeValueComponent>> {
+fn unwrapped_string(input: &str) -> IResult<&str, Vec<AttributeValueComponent<'_>>> {
     let (input, base) = alt((interpolated, raw_unwrapped))(input)?;
     let (input, mut rest) = many0(alt((interpolated, raw_unwrapped)))(input)?;
 
@@ -49,13 +48,19 @@ fn unwrapped_string(input: &str) -> IResult<&str, Vec<AttributeValueComponent>>
     Ok((input, rest))
 }
 
-pub fn parse(input: &str) -> IResult<&str, Vec<Attribute>> {
-    let attribute_name = take_while(|c: char| c.is_alphanumeric() || c == '-' || c == '_');
+fn attribute_name(input: &str) -> IResult<&str, &str> {
+    take_while1(|c: char| c.is_alphanumeric() || c == '-' || c == '_')(input)
+}
+
+pub fn parse(input: &str) -> IResult<&str, Vec<Attribute<'_>>> {
     let parse_pair = pair(
         terminated(attribute_name, tag("=")),
         alt((wrapped_string, unwrapped_string)),
     );
-    let parse_attribute = map(parse_pair, |(k, v)| Attribute::Custom(k, v));
+    let parse_attribute = alt((
+        map(parse_pair, |(k, v)| Attribute::Custom(k, v)),
+        map(attribute_name, Attribute::Boolean),
+    ));
 
     preceded(
         tag("("),
@@ -93,6 +98,31 @@ mod tests {
         );
     }
 
+    #[test]
+    fn boolean_attributes() {
+        assert_eq!(
+            super::parse("(type=checkbox checked)").unwrap(),
+            (
+                "",
+                vec![
+                    Attribute::Custom("type", vec![AttributeValueComponent::RawValue("checkbox")]),
+                    Attribute::Boolean("checked"),
+                ]
+            )
+        );
+
+        assert_eq!(
+            super::parse("(defer src=app.js)").unwrap(),
+            (
+                "",
+                vec![
+                    Attribute::Boolean("defer"),
+                    Attribute::Custom("src", vec![AttributeValueComponent::RawValue("app.js")]),
+                ]
+            )
+        );
+    }
+
     #[test]
     fn single_unwrapped_string() {
         assert_eq!(
@@ -103,10 +133,9 @@ mod tests {
         assert_eq!(
             (
                 "",
-                vec![AttributeValueComponent::InterpolatedValue(vec![
-                    Selector::Key("foo"),
-                    Selector::Key("bar")
-                ])]
+                vec![AttributeValueComponent::InterpolatedValue(
+                    vec![Selector::Key("foo"), Selector::Key("bar")].into()
+                )]
             ),
             super::unwrapped_string("{foo.bar}").unwrap()
         );
@@ -116,10 +145,9 @@ mod tests {
                 "",
                 vec![
                     AttributeValueComponent::RawValue("starting"),
-                    AttributeValueComponent::InterpolatedValue(vec![
-                        Selector::Key("foo"),
-                        Selector::Key("bar")
-                    ])
+                    AttributeValueComponent::InterpolatedValue(
+                        vec![Selector::Key("foo"), Selector::Key("bar")].into()
+                    )
                 ]
             ),
             super::unwrapped_string("starting{foo.bar}").unwrap()
@@ -139,10 +167,9 @@ mod tests {
         assert_eq!(
             (
                 "",
-                vec![AttributeValueComponent::InterpolatedValue(vec![
-                    Selector::Key("foo"),
-                    Selector::Key("bar")
-                ])]
+                vec![AttributeValueComponent::InterpolatedValue(
+                    vec![Selector::Key("foo"), Selector::Key("bar")].into()
+                )]
             ),
             super::wrapped_string("\"{foo.bar}\"").unwrap()
         );
@@ -152,10 +179,9 @@ mod tests {
                 "",
                 vec![
                     AttributeValueComponent::RawValue("starting"),
-                    AttributeValueComponent::InterpolatedValue(vec![
-                        Selector::Key("foo"),
-                        Selector::Key("bar")
-                    ])
+                    AttributeValueComponent::InterpolatedValue(
+                        vec![Selector::Key("foo"), Selector::Key("bar")].into()
+                    )
                 ]
             ),
             super::wrapped_string("\"starting{foo.bar}\"").unwrap()
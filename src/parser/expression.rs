@@ -0,0 +1,213 @@
+use super::super::{Comparator, Expression};
+use super::selector;
+use nom::{
+    branch::alt,
+    bytes::complete::{escaped, is_not, tag},
+    character::complete::{char, one_of, satisfy, space0, space1},
+    combinator::{map, map_res, not, opt, value},
+    multi::many0,
+    number::complete::recognize_float,
+    sequence::{delimited, pair, preceded, terminated, tuple},
+    IResult,
+};
+use serde_json::Value;
+
+pub fn parse(input: &str) -> IResult<&str, Expression<'_>> {
+    let (input, first) = parse_and(input)?;
+    let (input, rest) = many0(preceded(tuple((space1, tag("or"), space1)), parse_and))(input)?;
+
+    Ok((
+        input,
+        rest.into_iter()
+            .fold(first, |acc, e| Expression::Or(Box::new(acc), Box::new(e))),
+    ))
+}
+
+fn parse_and(input: &str) -> IResult<&str, Expression<'_>> {
+    let (input, first) = parse_not(input)?;
+    let (input, rest) = many0(preceded(tuple((space1, tag("and"), space1)), parse_not))(input)?;
+
+    Ok((
+        input,
+        rest.into_iter()
+            .fold(first, |acc, e| Expression::And(Box::new(acc), Box::new(e))),
+    ))
+}
+
+fn parse_not(input: &str) -> IResult<&str, Expression<'_>> {
+    alt((
+        map(preceded(pair(keyword("not"), space1), parse_not), |e| {
+            Expression::Not(Box::new(e))
+        }),
+        parse_comparison,
+    ))(input)
+}
+
+fn parse_comparison(input: &str) -> IResult<&str, Expression<'_>> {
+    let (input, left) = parse_operand(input)?;
+    let (input, comparison) = opt(pair(
+        delimited(space0, parse_comparator, space0),
+        parse_operand,
+    ))(input)?;
+
+    match comparison {
+        Some((comparator, right)) => Ok((
+            input,
+            Expression::Comparison(Box::new(left), comparator, Box::new(right)),
+        )),
+        None => Ok((input, left)),
+    }
+}
+
+fn parse_comparator(input: &str) -> IResult<&str, Comparator> {
+    alt((
+        value(Comparator::Equal, tag("==")),
+        value(Comparator::NotEqual, tag("!=")),
+        value(Comparator::LessThanOrEqual, tag("<=")),
+        value(Comparator::GreaterThanOrEqual, tag(">=")),
+        value(Comparator::LessThan, tag("<")),
+        value(Comparator::GreaterThan, tag(">")),
+    ))(input)
+}
+
+fn parse_operand(input: &str) -> IResult<&str, Expression<'_>> {
+    alt((
+        delimited(pair(char('('), space0), parse, pair(space0, char(')'))),
+        map(parse_literal, Expression::Literal),
+        map(selector::parse, Expression::Selector),
+    ))(input)
+}
+
+pub fn parse_literal(input: &str) -> IResult<&str, Value> {
+    alt((
+        map(parse_string, |v: &str| {
+            Value::String(v.replace("\\\"", "\""))
+        }),
+        map_res(
+            terminated(recognize_float, not(satisfy(|c| is_word(c) || c == '.'))),
+            parse_number,
+        ),
+        value(Value::Bool(true), keyword("true")),
+        value(Value::Bool(false), keyword("false")),
+        value(Value::Null, keyword("null")),
+    ))(input)
+}
+
+fn parse_number(input: &str) -> Result<Value, std::num::ParseFloatError> {
+    match input.parse::<i64>() {
+        Ok(v) => Ok(Value::from(v)),
+        Err(_) => input.parse::<f64>().map(Value::from),
+    }
+}
+
+fn parse_string(input: &str) -> IResult<&str, &str> {
+    alt((
+        delimited(
+            char('"'),
+            escaped(is_not("\\\"\n"), '\\', one_of("\"\\")),
+            char('"'),
+        ),
+        value("", tag("\"\"")),
+    ))(input)
+}
+
+fn keyword<'a>(word: &'static str) -> impl FnMut(&'a str) -> IResult<&'a str, &'a str> {
+    terminated(tag(word), not(satisfy(is_word)))
+}
+
+fn is_word(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '['
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::{context::Selector, Comparator, Expression};
+    use serde_json::Value;
+
+    fn selector(key: &str) -> Box<Expression<'_>> {
+        Box::new(Expression::Selector(vec![Selector::Key(key)]))
+    }
+
+    #[test]
+    fn selectors() {
+        assert_eq!(
+            super::parse("user.admin").unwrap(),
+            (
+                "",
+                Expression::Selector(vec![Selector::Key("user"), Selector::Key("admin")])
+            )
+        );
+    }
+
+    #[test]
+    fn negation() {
+        assert_eq!(
+            super::parse("not notice").unwrap(),
+            ("", Expression::Not(selector("notice")))
+        );
+    }
+
+    #[test]
+    fn comparisons() {
+        assert_eq!(
+            super::parse("status == \"draft\"").unwrap(),
+            (
+                "",
+                Expression::Comparison(
+                    selector("status"),
+                    Comparator::Equal,
+                    Box::new(Expression::Literal(Value::from("draft")))
+                )
+            )
+        );
+
+        assert_eq!(
+            super::parse("count>=2.5").unwrap(),
+            (
+                "",
+                Expression::Comparison(
+                    selector("count"),
+                    Comparator::GreaterThanOrEqual,
+                    Box::new(Expression::Literal(Value::from(2.5)))
+                )
+            )
+        );
+    }
+
+    #[test]
+    fn precedence() {
+        assert_eq!(
+            super::parse("a or b and not c").unwrap(),
+            (
+                "",
+                Expression::Or(
+                    selector("a"),
+                    Box::new(Expression::And(
+                        selector("b"),
+                        Box::new(Expression::Not(selector("c")))
+                    ))
+                )
+            )
+        );
+
+        assert_eq!(
+            super::parse("a and (b or c)").unwrap(),
+            (
+                "",
+                Expression::And(
+                    selector("a"),
+                    Box::new(Expression::Or(selector("b"), selector("c")))
+                )
+            )
+        );
+    }
+
+    #[test]
+    fn keywords_are_not_selectors() {
+        assert_eq!(
+            super::parse("order and android").unwrap(),
+            ("", Expression::And(selector("order"), selector("android")))
+        );
+        assert_eq!(super::parse("trueish").unwrap(), ("", *selector("trueish")));
+    }
+}
@@ -0,0 +1,90 @@
+use super::super::{context::Interpolation, Filter};
+use super::{expression, selector};
+use nom::{
+    bytes::complete::{tag, take_while1},
+    character::complete::{char, space0},
+    combinator::{map, opt},
+    multi::{many0, separated_list0},
+    sequence::{delimited, pair, preceded, tuple},
+    IResult,
+};
+
+pub fn parse(input: &str) -> IResult<&str, Interpolation<'_>> {
+    map(
+        pair(
+            selector::parse,
+            many0(preceded(tuple((space0, char('|'), space0)), parse_filter)),
+        ),
+        |(selectors, filters)| Interpolation { selectors, filters },
+    )(input)
+}
+
+pub fn braced(input: &str) -> IResult<&str, Interpolation<'_>> {
+    delimited(tag("{"), parse, tag("}"))(input)
+}
+
+fn parse_filter(input: &str) -> IResult<&str, Filter<'_>> {
+    let arguments = delimited(
+        pair(char('('), space0),
+        separated_list0(
+            tuple((space0, char(','), space0)),
+            expression::parse_literal,
+        ),
+        pair(space0, char(')')),
+    );
+
+    map(
+        pair(
+            take_while1(|c: char| c.is_alphanumeric() || c == '_'),
+            opt(arguments),
+        ),
+        |(name, arguments)| Filter {
+            name,
+            arguments: arguments.unwrap_or_default(),
+        },
+    )(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::{context::Selector, Filter};
+    use serde_json::Value;
+
+    #[test]
+    fn filter_pipelines() {
+        let (rest, interpolation) = super::parse("post.tags | join(\", \") | upcase").unwrap();
+
+        assert_eq!(rest, "");
+        assert_eq!(
+            interpolation.selectors,
+            vec![Selector::Key("post"), Selector::Key("tags")]
+        );
+        assert_eq!(
+            interpolation.filters,
+            vec![
+                Filter {
+                    name: "join",
+                    arguments: vec![Value::from(", ")]
+                },
+                Filter {
+                    name: "upcase",
+                    arguments: vec![]
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn braced_filters() {
+        let (rest, interpolation) = super::braced("{price|number(2)} each").unwrap();
+
+        assert_eq!(rest, " each");
+        assert_eq!(
+            interpolation.filters,
+            vec![Filter {
+                name: "number",
+                arguments: vec![Value::from(2)]
+            }]
+        );
+    }
+}
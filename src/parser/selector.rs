@@ -1,7 +1,7 @@
 use super::super::context::Selector;
 use nom::{
     branch::alt,
-    bytes::complete::{tag, take_while},
+    bytes::complete::{tag, take_while1},
     character::complete::digit1,
     combinator::{map, map_res, recognize},
     multi::many0,
@@ -9,7 +9,7 @@ use nom::{
     IResult,
 };
 
-pub fn parse(input: &str) -> IResult<&str, Vec<Selector>> {
+pub fn parse(input: &str) -> IResult<&str, Vec<Selector<'_>>> {
     let (input, first) = first_selector(input)?;
     let (input, mut selectors) = many0(subsequent_selector)(input)?;
 
@@ -17,7 +17,7 @@ pub fn parse(input: &str) -> IResult<&str, Vec<Selector>> {
     Ok((input, selectors))
 }
 
-fn first_selector(input: &str) -> IResult<&str, Selector> {
+fn first_selector(input: &str) -> IResult<&str, Selector<'_>> {
     alt((
         parse_selector_object_index,
         parse_selector_array_index,
@@ -25,7 +25,7 @@ fn first_selector(input: &str) -> IResult<&str, Selector> {
     ))(input)
 }
 
-fn subsequent_selector(input: &str) -> IResult<&str, Selector> {
+fn subsequent_selector(input: &str) -> IResult<&str, Selector<'_>> {
     alt((
         preceded(tag("."), parse_selector_object_index),
         parse_selector_array_index,
@@ -33,16 +33,19 @@ fn subsequent_selector(input: &str) -> IResult<&str, Selector> {
     ))(input)
 }
 
-fn parse_selector_object_index(input: &str) -> IResult<&str, Selector> {
+fn parse_selector_object_index(input: &str) -> IResult<&str, Selector<'_>> {
     map(parse_usize, Selector::Index)(input)
 }
 
-fn parse_selector_array_index(input: &str) -> IResult<&str, Selector> {
+fn parse_selector_array_index(input: &str) -> IResult<&str, Selector<'_>> {
     preceded(tag("["), terminated(parse_selector_object_index, tag("]")))(input)
 }
 
-fn parse_selector_key(input: &str) -> IResult<&str, Selector> {
-    map(take_while(|c: char| c.is_alphanumeric() || c == '_'), Selector::Key)(input)
+fn parse_selector_key(input: &str) -> IResult<&str, Selector<'_>> {
+    map(
+        take_while1(|c: char| c.is_alphanumeric() || c == '_'),
+        Selector::Key,
+    )(input)
 }
 
 fn parse_usize(input: &str) -> IResult<&str, usize> {
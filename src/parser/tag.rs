@@ -8,11 +8,11 @@ use nom::{
     IResult,
 };
 
-pub fn parse(input: &str) -> IResult<&str, Tag> {
+pub fn parse(input: &str) -> IResult<&str, Tag<'_>> {
     alt((parse_explicit_tag, parse_implicit_tag))(input)
 }
 
-fn parse_implicit_tag(input: &str) -> IResult<&str, Tag> {
+fn parse_implicit_tag(input: &str) -> IResult<&str, Tag<'_>> {
     let (input, mut attributes) = parse_attributes(input)?;
 
     let (input, custom_attributes) = opt(custom_attributes::parse)(input)?;
@@ -30,7 +30,7 @@ fn parse_implicit_tag(input: &str) -> IResult<&str, Tag> {
     ))
 }
 
-fn parse_explicit_tag(input: &str) -> IResult<&str, Tag> {
+fn parse_explicit_tag(input: &str) -> IResult<&str, Tag<'_>> {
     let (input, name) = preceded(tag("%"), take_while(|c: char| c.is_alphanumeric()))(input)?;
     let (input, mut attributes) = map(opt(parse_attributes), |v| v.unwrap_or(vec![]))(input)?;
     let (input, custom_attributes) = opt(custom_attributes::parse)(input)?;
@@ -41,7 +41,7 @@ fn parse_explicit_tag(input: &str) -> IResult<&str, Tag> {
     Ok((input, Tag { name, attributes }))
 }
 
-fn parse_attributes(input: &str) -> IResult<&str, Vec<Attribute>> {
+fn parse_attributes(input: &str) -> IResult<&str, Vec<Attribute<'_>>> {
     let parse_class = map(preceded(tag("."), parse_html_class), Attribute::Class);
     let parse_id = map(preceded(tag("#"), parse_html_class), Attribute::Id);
     let (input, attributes) = many1(alt((parse_class, parse_id)))(input)?;
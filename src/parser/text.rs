@@ -0,0 +1,53 @@
+use super::super::Node;
+use super::interpolation;
+use nom::{
+    branch::alt,
+    bytes::complete::{tag, take_till1},
+    combinator::map,
+    multi::many0,
+    IResult,
+};
+
+pub fn parse(input: &str) -> IResult<&str, Node<'_>> {
+    let (input, mut segments) = many0(alt((
+        map(interpolation::braced, Node::InterpolatedText),
+        map(take_till1(|c: char| c == '\n' || c == '{'), Node::Text),
+        map(tag("{"), Node::Text),
+    )))(input)?;
+
+    match segments.len() {
+        0 => Ok((input, Node::Text(""))),
+        1 => Ok((input, segments.remove(0))),
+        _ => Ok((input, Node::InterpolatedLine(segments))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::{context::Selector, Node};
+
+    #[test]
+    fn plain_text() {
+        match super::parse("Hello world\nnext") {
+            Ok(("\nnext", Node::Text("Hello world"))) => (),
+            _ => panic!("expected a single text node"),
+        }
+    }
+
+    #[test]
+    fn text_with_interpolation() {
+        match super::parse("Hello, {user.name}! {not interpolated}") {
+            Ok(("", Node::InterpolatedLine(segments))) => match segments.as_slice() {
+                [Node::Text("Hello, "), Node::InterpolatedText(interpolation), Node::Text("! "), Node::Text("{"), Node::Text("not interpolated}")] =>
+                {
+                    assert_eq!(
+                        interpolation,
+                        &vec![Selector::Key("user"), Selector::Key("name")].into()
+                    )
+                }
+                _ => panic!("unexpected segments"),
+            },
+            _ => panic!("expected an interpolated line"),
+        }
+    }
+}
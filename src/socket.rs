@@ -1,35 +1,54 @@
 use super::{
     context::{Context, ContextError},
     fragments::Fragments,
-    parser, styles, Builder, Nodes, Styles,
+    parser, styles, BlockScope, Builder, Diagnostic, NodeError, Nodes, Styles,
 };
+use serde_json::Value;
 use std::collections::HashMap;
 use std::path::PathBuf;
 
 pub struct Socket<'a> {
+    input: &'a str,
     nodes: Nodes<'a>,
     context: Context,
     fragments: Fragments<'a>,
+    fragment_sources: Vec<(&'a PathBuf, &'a str)>,
     styles: Styles,
+    strict: bool,
+}
+
+pub struct Rendered<'a> {
+    pub html: String,
+    pub errors: Vec<NodeError<'a>>,
 }
 
 #[derive(Debug)]
-pub enum SocketError<'a> {
-    ParseError(nom::Err<nom::error::Error<&'a str>>),
+pub enum SocketError {
+    IncompleteParse(Diagnostic),
+    ParseError(Diagnostic),
     StyleError(styles::SassCompileError),
     ContextError(ContextError),
 }
 
 impl<'a> Socket<'a> {
-    pub fn parse(input: &str) -> Result<Socket, SocketError> {
-        let (_, nodes) = parser::parse(input).map_err(SocketError::ParseError)?;
+    pub fn parse(input: &str) -> Result<Socket<'_>, SocketError> {
+        let nodes = match parser::parse(input) {
+            Ok(("", nodes)) => nodes,
+            Ok((rest, _)) => {
+                return Err(SocketError::IncompleteParse(Diagnostic::new(input, rest)))
+            }
+            Err(e) => return Err(SocketError::ParseError(Diagnostic::from_nom(input, e))),
+        };
         let context = Context::empty();
         let fragments = Fragments::default();
         Ok(Socket {
+            input,
             nodes,
             context,
             fragments,
+            fragment_sources: vec![],
             styles: Styles::default(),
+            strict: false,
         })
     }
 
@@ -40,7 +59,7 @@ impl<'a> Socket<'a> {
         if let Some(context_) = context {
             match context_ {
                 Ok(v) => {
-                    self.context = v;
+                    self.context = v.with_filters(self.context.filters());
                     Ok(self)
                 }
                 Err(e) => Err(SocketError::ContextError(e)),
@@ -50,30 +69,90 @@ impl<'a> Socket<'a> {
         }
     }
 
+    pub fn with_filter<F>(&mut self, name: &str, filter: F) -> &mut Self
+    where
+        F: Fn(&Value, &[Value]) -> Result<Value, String> + Send + Sync + 'static,
+    {
+        self.context.register_filter(name, filter);
+        self
+    }
+
     pub fn with_styles<T: Into<Styles>>(&mut self, styles: T) -> &mut Self {
         self.styles = styles.into();
         self
     }
 
+    pub fn with_strict(&mut self, strict: bool) -> &mut Self {
+        self.strict = strict;
+        self
+    }
+
     pub fn with_fragments(&mut self, frags: &'a HashMap<PathBuf, String>) -> &mut Self {
         for (k, v) in frags {
             self.fragments
                 .insert(k.to_path_buf(), Fragments::parse(k, v));
+            self.fragment_sources.push((k, v));
         }
 
         self
     }
 
-    pub fn to_html(&self) -> String {
-        self.nodes
+    pub fn render(&self) -> Rendered<'a> {
+        let (html, errors) = self
+            .nodes
             .to_html(
                 Builder::default(),
                 &self.context,
                 &self.fragments,
-                &HashMap::new(),
+                &BlockScope::default(),
                 &self.styles.as_option(),
+                &[],
             )
-            .result()
-            .join("")
+            .into_parts();
+
+        Rendered {
+            html: html.join(""),
+            errors,
+        }
+    }
+
+    pub fn try_render(&self) -> Result<Rendered<'a>, Vec<NodeError<'a>>> {
+        let rendered = self.render();
+
+        if self.strict && !rendered.errors.is_empty() {
+            Err(rendered.errors)
+        } else {
+            Ok(rendered)
+        }
+    }
+
+    pub fn to_html(&self) -> String {
+        self.render().html
     }
+
+    pub fn diagnostic(&self, error: &NodeError) -> Option<Diagnostic> {
+        if let NodeError::FragmentParseError(diagnostic) = error {
+            return Some(diagnostic.clone());
+        }
+
+        let fragment = error.source_fragment()?;
+
+        if contains(self.input, fragment) {
+            return Some(Diagnostic::warning(self.input, fragment, error.to_string()));
+        }
+
+        self.fragment_sources
+            .iter()
+            .find(|(_, source)| contains(source, fragment))
+            .map(|(path, source)| {
+                Diagnostic::warning(source, fragment, error.to_string()).with_path(path)
+            })
+    }
+}
+
+fn contains(source: &str, fragment: &str) -> bool {
+    let start = source.as_ptr() as usize;
+    let position = fragment.as_ptr() as usize;
+
+    position >= start && position + fragment.len() <= start + source.len()
 }
@@ -2,27 +2,25 @@ use sass_rs::{Options as SassOptions, OutputStyle};
 use std::path::Path;
 
 #[derive(Debug)]
-pub struct SassCompileError(String);
+pub struct SassCompileError(pub String);
 
 pub fn generate<P: AsRef<Path>>(path: P) -> Result<String, SassCompileError> {
-    let mut options = SassOptions::default();
-    options.output_style = OutputStyle::Compressed;
+    let options = SassOptions {
+        output_style: OutputStyle::Compressed,
+        ..SassOptions::default()
+    };
 
     sass_rs::compile_file(path, options).map_err(SassCompileError)
 }
 
+#[derive(Default)]
 pub enum Styles {
+    #[default]
     NotProcessed,
     StyleError(SassCompileError),
     Styles(String),
 }
 
-impl std::default::Default for Styles {
-    fn default() -> Self {
-        Styles::NotProcessed
-    }
-}
-
 impl Styles {
     pub fn as_option(&self) -> Option<String> {
         match self {
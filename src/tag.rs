@@ -1,4 +1,9 @@
-use super::{context::Context, Attribute, Attributes};
+use super::{context::Context, Attribute, Attributes, Builder, NodeError};
+
+const VOID_ELEMENTS: [&str; 13] = [
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
 
 pub struct Tag<'a> {
     pub name: &'a str,
@@ -6,17 +11,29 @@ pub struct Tag<'a> {
 }
 
 impl<'a> Tag<'a> {
-    pub fn open_tag_html(&self, context: &Context) -> String {
+    pub fn open_tag_html(
+        &self,
+        context: &Context,
+        builder: &mut Builder<String, NodeError<'a>>,
+    ) -> String {
         let attributes: Attributes = self.attributes.clone().into();
 
-        match attributes.to_html(context).as_slice() {
+        match attributes.to_html(context, builder).as_slice() {
             [] => format!("<{}>", self.name),
             attrs => format!("<{} {}>", self.name, attrs.join(" ")),
         }
     }
 
     pub fn close_tag_html(&self) -> String {
-        format!("</{}>", self.name)
+        if self.is_void() {
+            String::new()
+        } else {
+            format!("</{}>", self.name)
+        }
+    }
+
+    pub fn is_void(&self) -> bool {
+        VOID_ELEMENTS.contains(&self.name)
     }
 
     pub fn additional_markup(&self, styles: &Option<String>) -> String {
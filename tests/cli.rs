@@ -8,10 +8,99 @@ fn verify_behavior() {
     behavior_for("markdown");
     behavior_for("if_conditional");
     behavior_for("if_else_conditional");
+    behavior_for("if_expression");
     behavior_for("interpolation");
     behavior_for("attribute_interpolation");
     behavior_for("for_loops");
     behavior_for("fragments");
+    behavior_for("escaping");
+    behavior_for("boolean_attributes");
+    behavior_for("extends");
+}
+
+#[test]
+fn render_warnings_are_printed_to_stderr() {
+    let mut cmd = Command::cargo_bin("socket").unwrap();
+
+    cmd.arg("--context");
+    cmd.arg("tests/regression/context.json");
+
+    cmd.write_stdin("%ul\n  %li= people[0].nickname");
+
+    cmd.assert().success().stdout("<ul><li></li></ul>\n").stderr(
+        "warning: no value at `people[0].nickname`\n --> <stdin>:2:8\n  |\n2 |   %li= people[0].nickname\n  |        ^\n",
+    );
+}
+
+#[test]
+fn strict_mode_fails_on_render_errors() {
+    let mut cmd = Command::cargo_bin("socket").unwrap();
+
+    cmd.arg("--context");
+    cmd.arg("tests/regression/context.json");
+    cmd.arg("--strict");
+
+    cmd.write_stdin("%ul\n  - for person in people\n    %li= person.email_address\n%p= boolTrue");
+
+    cmd.assert().failure().stdout("").stderr(
+        "error: no value at `person.email_address`\n --> <stdin>:3:10\n  |\n3 |     %li= person.email_address\n  |          ^\n",
+    );
+}
+
+#[test]
+fn strict_mode_fails_on_attribute_errors() {
+    let mut cmd = Command::cargo_bin("socket").unwrap();
+
+    cmd.arg("--context");
+    cmd.arg("tests/regression/context.json");
+    cmd.arg("--strict");
+
+    cmd.write_stdin("%a(href={people[0].homepage}) Jane");
+
+    cmd.assert().failure().stdout("").stderr(
+        "error: no value at `people[0].homepage`\n --> <stdin>:1:10\n  |\n1 | %a(href={people[0].homepage}) Jane\n  |          ^\n",
+    );
+}
+
+#[test]
+fn strict_mode_fails_on_missing_conditions() {
+    let mut cmd = Command::cargo_bin("socket").unwrap();
+
+    cmd.arg("--context");
+    cmd.arg("tests/regression/context.json");
+    cmd.arg("--strict");
+
+    cmd.write_stdin("- if usr.admin\n  %p Admin");
+
+    cmd.assert().failure().stdout("").stderr(
+        "error: no value at `usr.admin`\n --> <stdin>:1:6\n  |\n1 | - if usr.admin\n  |      ^\n",
+    );
+}
+
+#[test]
+fn invalid_context_fails() {
+    let mut cmd = Command::cargo_bin("socket").unwrap();
+
+    cmd.arg("--context");
+    cmd.arg("tests/regression/invalid_context.json");
+
+    cmd.write_stdin("%p Hello");
+
+    cmd.assert()
+        .failure()
+        .stdout("")
+        .stderr("error: invalid context JSON: key must be a string at line 1 column 2\n");
+}
+
+#[test]
+fn incomplete_parse_reports_location() {
+    let mut cmd = Command::cargo_bin("socket").unwrap();
+
+    cmd.write_stdin("%section\n  %a(href=/about About");
+
+    cmd.assert().failure().stdout("").stderr(
+        "error: unterminated attribute list, expected `)`\n --> <stdin>:2:5\n  |\n2 |   %a(href=/about About\n  |     ^\n",
+    );
 }
 
 fn behavior_for(test: &str) {